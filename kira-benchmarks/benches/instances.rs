@@ -62,7 +62,7 @@ fn instances_benchmark(c: &mut Criterion) {
 				let mut instance_to_update = 0;
 				b.iter(|| {
 					instance_handles[instance_to_update]
-						.set_pitch(0.5..1.5)
+						.set_pitch(0.5..1.5, None)
 						.unwrap();
 					instance_to_update += 1;
 					instance_to_update %= NUM_INSTANCES;
@@ -1,7 +1,7 @@
 use indexmap::IndexMap;
 
 use crate::{
-	sound::{handle::SoundHandle, Sound, SoundId},
+	sound::{handle::SoundHandle, Sound, SoundId, WaveformBucket, WaveformSource},
 	util::inverse_lerp,
 	util::lerp,
 	Frame,
@@ -85,4 +85,35 @@ impl SoundClip {
 			Frame::from_mono(0.0)
 		}
 	}
+
+	/// Summarizes the part of the sound this clip plays between
+	/// `start` and `end` (in arrangement time).
+	///
+	/// Returns the summary and the fraction of the time range the
+	/// clip overlaps, or `None` if the clip doesn't overlap it.
+	pub(crate) fn waveform_bucket(
+		&self,
+		sound: &WaveformSource,
+		start: f64,
+		end: f64,
+	) -> Option<(WaveformBucket, f64)> {
+		let overlap_start = start.max(self.clip_time_range.0);
+		let overlap_end = end.min(self.clip_time_range.1);
+		if overlap_start >= overlap_end {
+			return None;
+		}
+		let to_sound_time = |time: f64| {
+			lerp(
+				self.sound_time_range.0,
+				self.sound_time_range.1,
+				inverse_lerp(self.clip_time_range.0, self.clip_time_range.1, time),
+			)
+		};
+		let sound_start = to_sound_time(overlap_start);
+		let sound_end = to_sound_time(overlap_end);
+		let bucket = sound
+			.summarize(sound_start.min(sound_end), sound_start.max(sound_end))
+			.bucket();
+		Some((bucket, (overlap_end - overlap_start) / (end - start)))
+	}
 }
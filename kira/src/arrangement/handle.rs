@@ -1,5 +1,7 @@
 //! An interface for controlling arrangements.
 
+use std::sync::Arc;
+
 use flume::Sender;
 use thiserror::Error;
 
@@ -9,7 +11,11 @@ use crate::{
 		handle::InstanceHandle, Instance, InstanceSettings, PauseInstanceSettings,
 		ResumeInstanceSettings, StopInstanceSettings,
 	},
-	mixer::TrackIndex,
+	mixer::{
+		effect::{Effect, EffectSettings},
+		TrackIndex,
+	},
+	Marker,
 };
 
 use super::{Arrangement, ArrangementId};
@@ -31,6 +37,7 @@ pub struct ArrangementHandle {
 	default_track: TrackIndex,
 	semantic_duration: Option<f64>,
 	default_loop_start: Option<f64>,
+	markers: Arc<Vec<Marker>>,
 	command_sender: Sender<Command>,
 }
 
@@ -42,6 +49,7 @@ impl ArrangementHandle {
 			default_track: arrangement.default_track(),
 			semantic_duration: arrangement.semantic_duration(),
 			default_loop_start: arrangement.default_loop_start(),
+			markers: Arc::new(arrangement.markers().to_vec()),
 			command_sender,
 		}
 	}
@@ -80,16 +88,51 @@ impl ArrangementHandle {
 		&mut self,
 		settings: InstanceSettings,
 	) -> Result<InstanceHandle, ArrangementHandleError> {
+		self.play_with_effects(settings, vec![])
+	}
+
+	/// Creates an instance of the arrangement and a handle
+	/// to control it without sending the instance to the
+	/// audio thread.
+	pub(crate) fn create_instance(&self, settings: InstanceSettings) -> (Instance, InstanceHandle) {
+		let (marker_event_sender, marker_event_receiver) =
+			flume::bounded(settings.event_queue_capacity);
+		let (event_sender, event_receiver) = flume::bounded(settings.event_queue_capacity);
 		let id = settings.id;
 		let instance = Instance::new(
 			self.id.into(),
 			self.duration,
 			None,
 			settings.into_internal(self.duration, self.default_loop_start, self.default_track),
+			Some(marker_event_sender),
+			Some(event_sender),
+		);
+		let handle = InstanceHandle::new(
+			id,
+			instance.public_state(),
+			instance.public_position(),
+			self.command_sender.clone(),
+			self.markers.clone(),
+			marker_event_receiver,
+			event_receiver,
 		);
-		let handle = InstanceHandle::new(id, instance.public_state(), self.command_sender.clone());
+		(instance, handle)
+	}
+
+	/// Plays the arrangement with a chain of effects that only
+	/// process the new instance's audio.
+	pub fn play_with_effects(
+		&mut self,
+		settings: InstanceSettings,
+		effects: Vec<(Box<dyn Effect>, EffectSettings)>,
+	) -> Result<InstanceHandle, ArrangementHandleError> {
+		let id = settings.id;
+		let (mut instance, handle) = self.create_instance(settings);
+		for (effect, effect_settings) in effects {
+			instance.add_effect(effect, effect_settings);
+		}
 		self.command_sender
-			.send(InstanceCommand::Play(id, instance).into())
+			.send(InstanceCommand::Play(id, Box::new(instance)).into())
 			.map_err(|_| ArrangementHandleError::BackendDisconnected)?;
 		Ok(handle)
 	}
@@ -138,9 +138,10 @@ use indexmap::IndexMap;
 
 use crate::{
 	group::{groups::Groups, GroupId, GroupSet},
+	instance::InstanceLimitBehavior,
 	mixer::TrackIndex,
-	sound::{handle::SoundHandle, Sound, SoundId},
-	Frame,
+	sound::{handle::SoundHandle, Sound, SoundId, WaveformBucket},
+	Frame, Marker,
 };
 
 /// An arrangement of sound clips to play at specific times.
@@ -154,12 +155,17 @@ pub struct Arrangement {
 	semantic_duration: Option<f64>,
 	default_loop_start: Option<f64>,
 	groups: GroupSet,
+	markers: Vec<Marker>,
+	max_instances: Option<usize>,
+	instance_limit_behavior: InstanceLimitBehavior,
 	cooldown_timer: f64,
 }
 
 impl Arrangement {
 	/// Creates a new, empty arrangement.
 	pub fn new(settings: ArrangementSettings) -> Self {
+		let mut markers = settings.markers;
+		markers.sort_by(|a, b| a.position.total_cmp(&b.position));
 		Self {
 			id: settings.id,
 			clips: vec![],
@@ -169,6 +175,9 @@ impl Arrangement {
 			semantic_duration: settings.semantic_duration,
 			default_loop_start: settings.default_loop_start,
 			groups: settings.groups,
+			markers,
+			max_instances: settings.max_instances,
+			instance_limit_behavior: settings.instance_limit_behavior,
 			cooldown_timer: 0.0,
 		}
 	}
@@ -190,6 +199,9 @@ impl Arrangement {
 			semantic_duration: settings.semantic_duration,
 			default_loop_start: Some(duration),
 			groups: settings.groups,
+			markers: settings.markers,
+			max_instances: settings.max_instances,
+			instance_limit_behavior: settings.instance_limit_behavior,
 		});
 		arrangement
 			.add_clip(SoundClip::new(sound_handle, 0.0))
@@ -223,6 +235,9 @@ impl Arrangement {
 			semantic_duration: settings.semantic_duration,
 			default_loop_start: Some(intro_duration + loop_duration),
 			groups: settings.groups,
+			markers: settings.markers,
+			max_instances: settings.max_instances,
+			instance_limit_behavior: settings.instance_limit_behavior,
 		});
 		arrangement
 			.add_clip(SoundClip::new(intro_sound_handle, 0.0))
@@ -275,6 +290,81 @@ impl Arrangement {
 		self.default_loop_start
 	}
 
+	/// Gets the markers of this arrangement, sorted by position.
+	pub fn markers(&self) -> &[Marker] {
+		&self.markers
+	}
+
+	/// Gets the maximum number of instances of this arrangement
+	/// that can play at the same time.
+	pub fn max_instances(&self) -> Option<usize> {
+		self.max_instances
+	}
+
+	/// Gets what happens when the arrangement is played while the
+	/// maximum number of instances are already playing.
+	pub fn instance_limit_behavior(&self) -> InstanceLimitBehavior {
+		self.instance_limit_behavior
+	}
+
+	/// Summarizes the audio of the arrangement between `start`
+	/// and `end` (in seconds) for drawing a waveform.
+	///
+	/// The time range is divided into `num_buckets` equal
+	/// spans, and a [`WaveformBucket`] is returned for each one.
+	/// `sounds` should contain the handles of the sounds used by
+	/// the arrangement's clips; clips whose sound isn't in the list
+	/// are skipped. Arrangements are moved to the audio thread when
+	/// they're added to the audio manager, so keep a clone of the
+	/// arrangement if you need to draw its waveform afterwards.
+	///
+	/// When clips overlap, the buckets are estimates: the minimums
+	/// and maximums of the clips are added together, and their RMS
+	/// values are combined as if the clips were uncorrelated.
+	pub fn waveform(
+		&self,
+		sounds: &[&SoundHandle],
+		start: f64,
+		end: f64,
+		num_buckets: usize,
+	) -> Vec<WaveformBucket> {
+		let clip_sounds: Vec<_> = self
+			.clips
+			.iter()
+			.filter_map(|clip| {
+				sounds
+					.iter()
+					.find(|sound| sound.id() == clip.sound_id)
+					.map(|sound| (clip, sound.waveform_source()))
+			})
+			.collect();
+		let bucket_duration = (end - start) / num_buckets as f64;
+		(0..num_buckets)
+			.map(|i| {
+				let bucket_start = start + bucket_duration * i as f64;
+				let bucket_end = bucket_start + bucket_duration;
+				let mut min = Frame::from_mono(0.0);
+				let mut max = Frame::from_mono(0.0);
+				let mut mean_square = (0.0, 0.0);
+				for (clip, sound) in &clip_sounds {
+					if let Some((bucket, weight)) =
+						clip.waveform_bucket(sound, bucket_start, bucket_end)
+					{
+						min += bucket.min;
+						max += bucket.max;
+						mean_square.0 += (bucket.rms.left as f64).powi(2) * weight;
+						mean_square.1 += (bucket.rms.right as f64).powi(2) * weight;
+					}
+				}
+				WaveformBucket {
+					min,
+					max,
+					rms: Frame::new(mean_square.0.sqrt() as f32, mean_square.1.sqrt() as f32),
+				}
+			})
+			.collect()
+	}
+
 	/// Gets the frame at the given position of the arrangement.
 	pub(crate) fn get_frame_at_position(
 		&self,
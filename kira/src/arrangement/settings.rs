@@ -1,4 +1,4 @@
-use crate::{group::GroupSet, mixer::TrackIndex};
+use crate::{group::GroupSet, instance::InstanceLimitBehavior, mixer::TrackIndex, Marker};
 
 use super::ArrangementId;
 
@@ -39,6 +39,15 @@ pub struct ArrangementSettings {
 	pub default_loop_start: Option<f64>,
 	/// The groups this arrangement belongs to.
 	pub groups: GroupSet,
+	/// Named points in time that instances of this arrangement
+	/// will emit events for when they're passed.
+	pub markers: Vec<Marker>,
+	/// The maximum number of instances of this arrangement that
+	/// can play at the same time.
+	pub max_instances: Option<usize>,
+	/// What should happen when the arrangement is played while the
+	/// maximum number of instances are already playing.
+	pub instance_limit_behavior: InstanceLimitBehavior,
 }
 
 impl ArrangementSettings {
@@ -94,6 +103,30 @@ impl ArrangementSettings {
 			..self
 		}
 	}
+
+	/// Sets the maximum number of instances of this arrangement that
+	/// can play at the same time.
+	pub fn max_instances(self, max_instances: usize) -> Self {
+		Self {
+			max_instances: Some(max_instances),
+			..self
+		}
+	}
+
+	/// Sets what should happen when the arrangement is played while the
+	/// maximum number of instances are already playing.
+	pub fn instance_limit_behavior(self, instance_limit_behavior: InstanceLimitBehavior) -> Self {
+		Self {
+			instance_limit_behavior,
+			..self
+		}
+	}
+
+	/// Adds a named marker at the given position (in seconds).
+	pub fn marker(mut self, position: f64, name: impl Into<String>) -> Self {
+		self.markers.push(Marker::new(position, name));
+		self
+	}
 }
 
 impl Default for ArrangementSettings {
@@ -105,6 +138,9 @@ impl Default for ArrangementSettings {
 			semantic_duration: None,
 			default_loop_start: None,
 			groups: GroupSet::new(),
+			markers: vec![],
+			max_instances: None,
+			instance_limit_behavior: InstanceLimitBehavior::default(),
 		}
 	}
 }
@@ -142,6 +178,15 @@ pub struct LoopArrangementSettings {
 	pub semantic_duration: Option<f64>,
 	/// The groups this arrangement belongs to.
 	pub groups: GroupSet,
+	/// Named points in time that instances of this arrangement
+	/// will emit events for when they're passed.
+	pub markers: Vec<Marker>,
+	/// The maximum number of instances of this arrangement that
+	/// can play at the same time.
+	pub max_instances: Option<usize>,
+	/// What should happen when the arrangement is played while the
+	/// maximum number of instances are already playing.
+	pub instance_limit_behavior: InstanceLimitBehavior,
 }
 
 impl LoopArrangementSettings {
@@ -189,6 +234,30 @@ impl LoopArrangementSettings {
 			..self
 		}
 	}
+
+	/// Sets the maximum number of instances of this arrangement that
+	/// can play at the same time.
+	pub fn max_instances(self, max_instances: usize) -> Self {
+		Self {
+			max_instances: Some(max_instances),
+			..self
+		}
+	}
+
+	/// Sets what should happen when the arrangement is played while the
+	/// maximum number of instances are already playing.
+	pub fn instance_limit_behavior(self, instance_limit_behavior: InstanceLimitBehavior) -> Self {
+		Self {
+			instance_limit_behavior,
+			..self
+		}
+	}
+
+	/// Adds a named marker at the given position (in seconds).
+	pub fn marker(mut self, position: f64, name: impl Into<String>) -> Self {
+		self.markers.push(Marker::new(position, name));
+		self
+	}
 }
 
 impl Default for LoopArrangementSettings {
@@ -199,6 +268,9 @@ impl Default for LoopArrangementSettings {
 			cooldown: Some(0.0001),
 			semantic_duration: None,
 			groups: GroupSet::new(),
+			markers: vec![],
+			max_instances: None,
+			instance_limit_behavior: InstanceLimitBehavior::default(),
 		}
 	}
 }
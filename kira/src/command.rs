@@ -3,7 +3,8 @@ use crate::{
 	audio_stream::{AudioStream, AudioStreamId},
 	group::{Group, GroupId},
 	instance::{
-		Instance, InstanceId, PauseInstanceSettings, ResumeInstanceSettings, StopInstanceSettings,
+		Instance, InstanceId, InstanceTiming, InternalCrossfadeSettings, PauseInstanceSettings,
+		ResumeInstanceSettings, StopInstanceSettings,
 	},
 	metronome::{Metronome, MetronomeId},
 	mixer::{
@@ -14,6 +15,7 @@ use crate::{
 	playable::PlayableId,
 	sequence::{SequenceInstance, SequenceInstanceId},
 	sound::{Sound, SoundId},
+	spatial::{Emitter, EmitterId, HrirSet, Vector3},
 	tempo::Tempo,
 	value::Value,
 };
@@ -21,22 +23,37 @@ use crate::{
 #[derive(Debug, Clone)]
 pub(crate) enum ResourceCommand {
 	AddSound(Sound),
+	#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+	ReloadSound(Sound),
 	RemoveSound(SoundId),
 	AddArrangement(Arrangement),
 	RemoveArrangement(ArrangementId),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub(crate) enum InstanceCommand {
-	Play(InstanceId, Instance),
-	SetInstanceVolume(InstanceId, Value<f64>),
-	SetInstancePitch(InstanceId, Value<f64>),
-	SetInstancePanning(InstanceId, Value<f64>),
+	Play(InstanceId, Box<Instance>),
+	Crossfade(
+		InstanceId,
+		InstanceId,
+		Box<Instance>,
+		InternalCrossfadeSettings,
+	),
+	SetInstanceVolume(InstanceId, Value<f64>, Option<Tween>),
+	SetInstancePitch(InstanceId, Value<f64>, Option<Tween>),
+	SetInstancePanning(InstanceId, Value<f64>, Option<Tween>),
+	SetInstanceSpeed(InstanceId, Value<f64>, Option<Tween>),
+	SetInstanceWidth(InstanceId, Value<f64>, Option<Tween>),
+	SetInstanceSendVolume(InstanceId, TrackIndex, Value<f64>, Option<Tween>),
 	SeekInstance(InstanceId, f64),
 	SeekInstanceTo(InstanceId, f64),
 	PauseInstance(InstanceId, PauseInstanceSettings),
 	ResumeInstance(InstanceId, ResumeInstanceSettings),
 	StopInstance(InstanceId, StopInstanceSettings),
+	ReleaseInstanceLoop(InstanceId),
+	AddInstanceEffect(InstanceId, Box<dyn Effect>, EffectSettings),
+	SetInstanceEffectEnabled(InstanceId, EffectId, bool),
+	RemoveInstanceEffect(InstanceId, EffectId),
 	PauseInstancesOf(PlayableId, PauseInstanceSettings),
 	ResumeInstancesOf(PlayableId, ResumeInstanceSettings),
 	StopInstancesOf(PlayableId, StopInstanceSettings),
@@ -48,6 +65,29 @@ pub(crate) enum InstanceCommand {
 	StopGroup(GroupId, StopInstanceSettings),
 }
 
+impl InstanceCommand {
+	/// Returns when the command should take effect.
+	pub fn timing(&self) -> InstanceTiming {
+		match self {
+			InstanceCommand::Play(_, instance) => instance.start_timing(),
+			InstanceCommand::Crossfade(_, _, _, settings) => settings.timing,
+			InstanceCommand::PauseInstance(_, settings)
+			| InstanceCommand::PauseInstancesOf(_, settings)
+			| InstanceCommand::PauseInstancesOfSequence(_, settings)
+			| InstanceCommand::PauseGroup(_, settings) => settings.timing,
+			InstanceCommand::ResumeInstance(_, settings)
+			| InstanceCommand::ResumeInstancesOf(_, settings)
+			| InstanceCommand::ResumeInstancesOfSequence(_, settings)
+			| InstanceCommand::ResumeGroup(_, settings) => settings.timing,
+			InstanceCommand::StopInstance(_, settings)
+			| InstanceCommand::StopInstancesOf(_, settings)
+			| InstanceCommand::StopInstancesOfSequence(_, settings)
+			| InstanceCommand::StopGroup(_, settings) => settings.timing,
+			_ => InstanceTiming::default(),
+		}
+	}
+}
+
 #[derive(Debug, Clone)]
 pub(crate) enum MetronomeCommand {
 	AddMetronome(MetronomeId, Metronome),
@@ -92,6 +132,18 @@ pub(crate) enum GroupCommand {
 	RemoveGroup(GroupId),
 }
 
+#[derive(Debug, Clone)]
+pub(crate) enum SpatialCommand {
+	AddEmitter(EmitterId, Box<Emitter>),
+	RemoveEmitter(EmitterId),
+	SetEmitterPosition(EmitterId, Vector3),
+	SetEmitterVelocity(EmitterId, Option<Vector3>),
+	SetListenerPosition(Vector3),
+	SetListenerVelocity(Option<Vector3>),
+	SetHrirSet(Option<HrirSet>),
+	SetListenerOrientation(Vector3, Vector3),
+}
+
 #[derive(Debug)]
 pub(crate) enum StreamCommand {
 	AddStream(AudioStreamId, TrackIndex, Box<dyn AudioStream>),
@@ -107,6 +159,7 @@ pub(crate) enum Command {
 	Parameter(ParameterCommand),
 	Group(GroupCommand),
 	Stream(StreamCommand),
+	Spatial(SpatialCommand),
 }
 
 impl From<ResourceCommand> for Command {
@@ -156,3 +209,9 @@ impl From<StreamCommand> for Command {
 		Self::Stream(command)
 	}
 }
+
+impl From<SpatialCommand> for Command {
+	fn from(command: SpatialCommand) -> Self {
+		Self::Spatial(command)
+	}
+}
@@ -1,5 +1,7 @@
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
+use crate::PanLaw;
+
 /// An audio sample with a left and right channel.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Frame {
@@ -23,17 +25,59 @@ impl Frame {
 
 	/// Creates a frame from `i32`s with the given bit depth.
 	pub fn from_i32(left: i32, right: i32, bit_depth: u32) -> Self {
-		let max_int = (1 << bit_depth) / 2;
-		let scale = 1.0 / max_int as f32;
+		let scale = Self::int_sample_scale(bit_depth);
 		Self::new(left as f32 * scale, right as f32 * scale)
 	}
 
+	/// Gets the factor that converts an integer sample with the
+	/// given bit depth to a float sample from -1 to 1.
+	pub(crate) fn int_sample_scale(bit_depth: u32) -> f32 {
+		let max_int = (1 << bit_depth) / 2;
+		1.0 / max_int as f32
+	}
+
 	/// Pans a frame to the left or right.
 	///
 	/// An `x` of 0 represents a hard left panning, an `x` of 1
 	/// represents a hard right panning.
 	pub fn panned(self, x: f32) -> Self {
-		Self::new(self.left * (1.0 - x).sqrt(), self.right * x.sqrt())
+		self.panned_with_law(x, PanLaw::ConstantPower)
+	}
+
+	/// Pans a frame to the left or right using a [`PanLaw`].
+	///
+	/// Each channel is only scaled, so a hard pan drops
+	/// the opposite channel.
+	pub fn panned_with_law(self, x: f32, law: PanLaw) -> Self {
+		let (left, right) = law.gains(x);
+		Self::new(self.left * left, self.right * right)
+	}
+
+	/// Moves a stereo frame to the left or right while
+	/// keeping both of its channels.
+	///
+	/// At an `x` of 0.5, the frame is unchanged. As `x` moves
+	/// toward 1, the left channel is panned toward the right
+	/// until both channels come out of the right speaker
+	/// (and vice versa).
+	pub fn balanced(self, x: f32, law: PanLaw) -> Self {
+		let x = x.clamp(0.0, 1.0);
+		let (left_to_left, left_to_right) = law.gains((2.0 * x - 1.0).max(0.0));
+		let (right_to_left, right_to_right) = law.gains((2.0 * x).min(1.0));
+		Self::new(
+			self.left * left_to_left + self.right * right_to_left,
+			self.left * left_to_right + self.right * right_to_right,
+		)
+	}
+
+	/// Changes the stereo width of a frame.
+	///
+	/// A `width` of 0 makes the frame mono, 1 leaves it
+	/// unchanged, and values above 1 make it wider.
+	pub fn with_width(self, width: f32) -> Self {
+		let mid = (self.left + self.right) / 2.0;
+		let side = (self.left - self.right) / 2.0 * width;
+		Self::new(mid + side, mid - side)
 	}
 }
 
@@ -104,3 +148,18 @@ impl Neg for Frame {
 		Self::new(-self.left, -self.right)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn balanced() {
+		let frame = Frame::new(0.25, 0.75);
+		for &law in &[PanLaw::ConstantPower, PanLaw::Linear] {
+			assert_eq!(frame.balanced(0.0, law), Frame::new(1.0, 0.0));
+			assert_eq!(frame.balanced(0.5, law), frame);
+			assert_eq!(frame.balanced(1.0, law), Frame::new(0.0, 1.0));
+		}
+	}
+}
@@ -0,0 +1,143 @@
+use std::f64::consts::PI;
+
+use nanorand::{tls_rng, RNG};
+
+use crate::{parameter::Parameters, value::CachedValue, Frame};
+
+use super::GranularSettings;
+
+/// The maximum number of grains that can play at once.
+/// New grains are skipped until an old one finishes.
+const MAX_GRAINS: usize = 64;
+/// The shortest a grain can be (in seconds).
+const MIN_GRAIN_SIZE: f64 = 0.001;
+
+/// Returns a random number between -1 and 1.
+fn random_bipolar() -> f64 {
+	f64::from(tls_rng().generate::<u32>()) / f64::from(u32::MAX) * 2.0 - 1.0
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Grain {
+	/// The position in the audio the grain started at (in seconds).
+	start: f64,
+	/// How fast the grain moves through the audio.
+	rate: f64,
+	/// How long the grain plays for (in seconds).
+	duration: f64,
+	/// How long the grain has been playing (in seconds).
+	age: f64,
+}
+
+impl Grain {
+	fn finished(&self) -> bool {
+		self.age >= self.duration
+	}
+
+	fn read_position(&self) -> f64 {
+		self.start + self.age * self.rate
+	}
+
+	/// The Hann window used to fade the grain in and out.
+	fn window(&self) -> f32 {
+		(0.5 - 0.5 * (2.0 * PI * self.age / self.duration).cos()) as f32
+	}
+}
+
+/// Plays audio as a stream of short, overlapping grains
+/// read from around a scan position.
+#[derive(Debug, Clone)]
+pub(crate) struct GranularPlayer {
+	grain_size: CachedValue<f64>,
+	density: CachedValue<f64>,
+	scan_position: CachedValue<f64>,
+	position_jitter: CachedValue<f64>,
+	pitch_jitter: CachedValue<f64>,
+	grains: [Option<Grain>; MAX_GRAINS],
+	time_until_next_grain: f64,
+}
+
+impl GranularPlayer {
+	pub fn new(settings: GranularSettings) -> Self {
+		Self {
+			grain_size: CachedValue::new(settings.grain_size, 0.1),
+			density: CachedValue::new(settings.density, 20.0),
+			scan_position: CachedValue::new(settings.scan_position, 0.0),
+			position_jitter: CachedValue::new(settings.position_jitter, 0.0),
+			pitch_jitter: CachedValue::new(settings.pitch_jitter, 0.0),
+			grains: [None; MAX_GRAINS],
+			time_until_next_grain: 0.0,
+		}
+	}
+
+	pub fn scan_position(&self) -> f64 {
+		self.scan_position.value()
+	}
+
+	pub fn update(&mut self, parameters: &Parameters) {
+		self.grain_size.update(parameters);
+		self.density.update(parameters);
+		self.scan_position.update(parameters);
+		self.position_jitter.update(parameters);
+		self.pitch_jitter.update(parameters);
+	}
+
+	fn start_grain(&mut self, rate: f64) {
+		let slot = match self
+			.grains
+			.iter()
+			.position(|grain| grain.map_or(true, |grain| grain.finished()))
+		{
+			Some(slot) => slot,
+			None => return,
+		};
+		let semitones = self.pitch_jitter.value() * random_bipolar();
+		self.grains[slot] = Some(Grain {
+			start: self.scan_position.value() + self.position_jitter.value() * random_bipolar(),
+			rate: rate * 2.0f64.powf(semitones / 12.0),
+			duration: self.grain_size.value().max(MIN_GRAIN_SIZE),
+			age: 0.0,
+		});
+	}
+
+	/// Produces the next frame of audio.
+	///
+	/// - `rate` is how fast new grains should move through the audio,
+	///   which is negative for reverse playback
+	/// - `read` gets the frame of audio at a position
+	pub fn process(&mut self, dt: f64, rate: f64, read: impl Fn(f64) -> Frame) -> Frame {
+		let density = self.density.value();
+		if density > 0.0 {
+			// at most one grain starts per frame, so very high
+			// densities don't stall the audio thread
+			if self.time_until_next_grain <= 0.0 {
+				self.start_grain(rate);
+				self.time_until_next_grain = (self.time_until_next_grain + 1.0 / density).max(0.0);
+			}
+			self.time_until_next_grain -= dt;
+		} else {
+			self.time_until_next_grain = 0.0;
+		}
+		let mut out = Frame::from_mono(0.0);
+		for grain in self.grains.iter_mut().flatten() {
+			if !grain.finished() {
+				out += read(grain.read_position()) * grain.window();
+				grain.age += dt;
+			}
+		}
+		out * self.gain()
+	}
+
+	/// Returns the volume that keeps the output level steady
+	/// as the number of overlapping grains changes.
+	///
+	/// On average, `density * grain_size` grains overlap, and
+	/// the Hann window averages 0.5, so the sum of the grains is
+	/// scaled down by half of the overlap. Sparse grains that
+	/// don't overlap aren't made louder.
+	fn gain(&self) -> f32 {
+		let overlap = (self.density.value() * self.grain_size.value().max(MIN_GRAIN_SIZE))
+			.min(MAX_GRAINS as f64);
+		(1.0 / (overlap / 2.0).max(1.0)) as f32
+	}
+}
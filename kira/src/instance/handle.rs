@@ -3,16 +3,22 @@
 use std::sync::Arc;
 
 use atomic::{Atomic, Ordering};
-use flume::Sender;
+use flume::{Receiver, Sender};
 use thiserror::Error;
 
 use crate::{
 	command::{Command, InstanceCommand},
-	Value,
+	mixer::{
+		effect::{Effect, EffectId, EffectSettings},
+		TrackIndex,
+	},
+	parameter::tween::Tween,
+	Marker, Tempo, Value,
 };
 
 use super::{
-	InstanceId, InstanceState, PauseInstanceSettings, ResumeInstanceSettings, StopInstanceSettings,
+	InstanceEvent, InstanceId, InstanceState, PauseInstanceSettings, ResumeInstanceSettings,
+	StopInstanceSettings,
 };
 
 /// Something that can go wrong when using an [`InstanceHandle`] to
@@ -29,19 +35,31 @@ pub enum InstanceHandleError {
 pub struct InstanceHandle {
 	id: InstanceId,
 	state: Arc<Atomic<InstanceState>>,
+	position: Arc<Atomic<f64>>,
 	command_sender: Sender<Command>,
+	markers: Arc<Vec<Marker>>,
+	marker_event_receiver: Receiver<usize>,
+	event_receiver: Receiver<InstanceEvent>,
 }
 
 impl InstanceHandle {
 	pub(crate) fn new(
 		id: InstanceId,
 		state: Arc<Atomic<InstanceState>>,
+		position: Arc<Atomic<f64>>,
 		command_sender: Sender<Command>,
+		markers: Arc<Vec<Marker>>,
+		marker_event_receiver: Receiver<usize>,
+		event_receiver: Receiver<InstanceEvent>,
 	) -> Self {
 		Self {
 			id,
 			state,
+			position,
 			command_sender,
+			markers,
+			marker_event_receiver,
+			event_receiver,
 		}
 	}
 
@@ -55,27 +73,94 @@ impl InstanceHandle {
 		self.state.load(Ordering::Relaxed)
 	}
 
-	/// Sets the volume of the instance.
-	pub fn set_volume(&mut self, volume: impl Into<Value<f64>>) -> Result<(), InstanceHandleError> {
+	/// Returns the playback position of the instance (in seconds)
+	/// as of the last frame the audio thread processed.
+	pub fn position(&self) -> f64 {
+		self.position.load(Ordering::Relaxed)
+	}
+
+	/// Returns the playback position of the instance in beats
+	/// at the given tempo.
+	pub fn position_in_beats(&self, tempo: impl Into<Tempo>) -> f64 {
+		tempo.into().seconds_to_beats(self.position())
+	}
+
+	/// Sets the volume of the instance with an optional tween.
+	pub fn set_volume(
+		&mut self,
+		volume: impl Into<Value<f64>>,
+		tween: impl Into<Option<Tween>>,
+	) -> Result<(), InstanceHandleError> {
 		self.command_sender
-			.send(InstanceCommand::SetInstanceVolume(self.id, volume.into()).into())
+			.send(InstanceCommand::SetInstanceVolume(self.id, volume.into(), tween.into()).into())
 			.map_err(|_| InstanceHandleError::BackendDisconnected)
 	}
 
-	/// Sets the pitch of the instance.
-	pub fn set_pitch(&mut self, pitch: impl Into<Value<f64>>) -> Result<(), InstanceHandleError> {
+	/// Sets the pitch of the instance with an optional tween.
+	pub fn set_pitch(
+		&mut self,
+		pitch: impl Into<Value<f64>>,
+		tween: impl Into<Option<Tween>>,
+	) -> Result<(), InstanceHandleError> {
 		self.command_sender
-			.send(InstanceCommand::SetInstancePitch(self.id, pitch.into()).into())
+			.send(InstanceCommand::SetInstancePitch(self.id, pitch.into(), tween.into()).into())
 			.map_err(|_| InstanceHandleError::BackendDisconnected)
 	}
 
-	/// Sets the panning of the instance.
+	/// Sets the panning of the instance with an optional tween.
 	pub fn set_panning(
 		&mut self,
 		panning: impl Into<Value<f64>>,
+		tween: impl Into<Option<Tween>>,
 	) -> Result<(), InstanceHandleError> {
 		self.command_sender
-			.send(InstanceCommand::SetInstancePanning(self.id, panning.into()).into())
+			.send(InstanceCommand::SetInstancePanning(self.id, panning.into(), tween.into()).into())
+			.map_err(|_| InstanceHandleError::BackendDisconnected)
+	}
+
+	/// Sets the speed of the instance with an optional tween.
+	pub fn set_speed(
+		&mut self,
+		speed: impl Into<Value<f64>>,
+		tween: impl Into<Option<Tween>>,
+	) -> Result<(), InstanceHandleError> {
+		self.command_sender
+			.send(InstanceCommand::SetInstanceSpeed(self.id, speed.into(), tween.into()).into())
+			.map_err(|_| InstanceHandleError::BackendDisconnected)
+	}
+
+	/// Sets the stereo width of the instance with an optional tween.
+	pub fn set_width(
+		&mut self,
+		width: impl Into<Value<f64>>,
+		tween: impl Into<Option<Tween>>,
+	) -> Result<(), InstanceHandleError> {
+		self.command_sender
+			.send(InstanceCommand::SetInstanceWidth(self.id, width.into(), tween.into()).into())
+			.map_err(|_| InstanceHandleError::BackendDisconnected)
+	}
+
+	/// Sets the volume of the instance's send to a track
+	/// with an optional tween.
+	///
+	/// This has no effect if the instance doesn't have a send
+	/// to the track.
+	pub fn set_send_volume(
+		&mut self,
+		track: impl Into<TrackIndex>,
+		volume: impl Into<Value<f64>>,
+		tween: impl Into<Option<Tween>>,
+	) -> Result<(), InstanceHandleError> {
+		self.command_sender
+			.send(
+				InstanceCommand::SetInstanceSendVolume(
+					self.id,
+					track.into(),
+					volume.into(),
+					tween.into(),
+				)
+				.into(),
+			)
 			.map_err(|_| InstanceHandleError::BackendDisconnected)
 	}
 
@@ -94,6 +179,10 @@ impl InstanceHandle {
 	}
 
 	/// Pauses the instance.
+	///
+	/// If `settings.timing` makes the pause wait, it's dropped when
+	/// [`num_instances`](crate::manager::AudioManagerSettings::num_instances)
+	/// commands are already waiting.
 	pub fn pause(&mut self, settings: PauseInstanceSettings) -> Result<(), InstanceHandleError> {
 		self.command_sender
 			.send(InstanceCommand::PauseInstance(self.id, settings).into())
@@ -101,6 +190,10 @@ impl InstanceHandle {
 	}
 
 	/// Resumes the instance.
+	///
+	/// If `settings.timing` makes the resume wait, it's dropped when
+	/// [`num_instances`](crate::manager::AudioManagerSettings::num_instances)
+	/// commands are already waiting.
 	pub fn resume(&mut self, settings: ResumeInstanceSettings) -> Result<(), InstanceHandleError> {
 		self.command_sender
 			.send(InstanceCommand::ResumeInstance(self.id, settings).into())
@@ -108,9 +201,72 @@ impl InstanceHandle {
 	}
 
 	/// Stops the instance.
+	///
+	/// If `settings.timing` makes the stop wait, it's dropped when
+	/// [`num_instances`](crate::manager::AudioManagerSettings::num_instances)
+	/// commands are already waiting.
 	pub fn stop(&mut self, settings: StopInstanceSettings) -> Result<(), InstanceHandleError> {
 		self.command_sender
 			.send(InstanceCommand::StopInstance(self.id, settings).into())
 			.map_err(|_| InstanceHandleError::BackendDisconnected)
 	}
+
+	/// Stops the instance from looping. The instance will finish
+	/// its current pass through the loop region and then continue
+	/// playing past the loop end point.
+	pub fn release_loop(&mut self) -> Result<(), InstanceHandleError> {
+		self.command_sender
+			.send(InstanceCommand::ReleaseInstanceLoop(self.id).into())
+			.map_err(|_| InstanceHandleError::BackendDisconnected)
+	}
+
+	/// Adds an effect to the end of the instance's effect chain.
+	///
+	/// The effect chain processes the instance's audio after
+	/// it's panned and before the instance's volume and fades
+	/// are applied.
+	pub fn add_effect(
+		&mut self,
+		effect: impl Effect + 'static,
+		settings: EffectSettings,
+	) -> Result<EffectId, InstanceHandleError> {
+		let id = settings.id;
+		self.command_sender
+			.send(InstanceCommand::AddInstanceEffect(self.id, Box::new(effect), settings).into())
+			.map_err(|_| InstanceHandleError::BackendDisconnected)?;
+		Ok(id)
+	}
+
+	/// Sets whether one of the instance's effects is enabled.
+	pub fn set_effect_enabled(
+		&mut self,
+		id: impl Into<EffectId>,
+		enabled: bool,
+	) -> Result<(), InstanceHandleError> {
+		self.command_sender
+			.send(InstanceCommand::SetInstanceEffectEnabled(self.id, id.into(), enabled).into())
+			.map_err(|_| InstanceHandleError::BackendDisconnected)
+	}
+
+	/// Removes an effect from the instance.
+	pub fn remove_effect(&mut self, id: impl Into<EffectId>) -> Result<(), InstanceHandleError> {
+		self.command_sender
+			.send(InstanceCommand::RemoveInstanceEffect(self.id, id.into()).into())
+			.map_err(|_| InstanceHandleError::BackendDisconnected)
+	}
+
+	/// Gets the first marker that this instance passed
+	/// since the last call to `pop_marker`.
+	pub fn pop_marker(&mut self) -> Option<&Marker> {
+		match self.marker_event_receiver.try_recv().ok() {
+			Some(index) => self.markers.get(index),
+			None => None,
+		}
+	}
+
+	/// Gets the first event that happened to this instance
+	/// since the last call to `pop_event`.
+	pub fn pop_event(&mut self) -> Option<InstanceEvent> {
+		self.event_receiver.try_recv().ok()
+	}
 }
@@ -33,6 +33,29 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 //!
+//! ### Starting a sound on the next bar
+//!
+//! ```no_run
+//! # use kira::{
+//! # 	manager::AudioManager,
+//! # 	instance::{InstanceSettings, InstanceTiming},
+//! # 	metronome::MetronomeSettings,
+//! # 	sound::Sound,
+//! # };
+//! #
+//! # let mut audio_manager = AudioManager::new(Default::default())?;
+//! # let mut sound = audio_manager.add_sound(Sound::from_file("stinger.ogg", Default::default())?)?;
+//! # let mut metronome = audio_manager.add_metronome(MetronomeSettings::new())?;
+//! # metronome.start()?;
+//! let instance_handle = sound.play(
+//! 	InstanceSettings::new().start_timing(InstanceTiming::new().quantize(&metronome, 4.0)),
+//! )?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Pausing, resuming, and stopping instances can be
+//! delayed and quantized the same way.
+//!
 //! ## Reverse playback and loop points
 //!
 //! There are two ways to enable reverse playback:
@@ -56,11 +79,217 @@
 //! backward, when the playback position is earlier than the
 //! loop start point, it will wrap around to the end
 //! of the instance.
+//!
+//! By default, the loop region ends at the end of the sound or
+//! arrangement, but you can set an earlier loop end point so
+//! that the audio after it plays once the instance stops looping.
+//! An instance stops looping after repeating the loop region the
+//! number of times set by [`InstanceSettings::loop_count`], or
+//! when [`InstanceHandle::release_loop`] is called. The loop region
+//! only takes effect once the playback position has entered it, so
+//! seeking past the loop end point plays the rest of the audio.
+//!
+//! If the audio doesn't loop seamlessly, you can set
+//! [`InstanceSettings::loop_crossfade`] to blend the audio
+//! before the loop end point with the audio before the loop
+//! start point, so the jump back isn't audible.
+//!
+//! ## Panning and stereo width
+//!
+//! By default, instances are panned with a constant power
+//! [`PanLaw`], which makes centered sounds 3 dB quieter than
+//! sounds panned hard left or right. [`InstanceSettings::pan_law`]
+//! picks a different law, like [`PanLaw::ZeroDbCenter`], which
+//! keeps centered sounds at full volume.
+//!
+//! Panning normally turns down one channel of a stereo sound, so
+//! panning it hard left drops its right channel entirely. Enabling
+//! [`InstanceSettings::balance`] moves both channels toward one
+//! side instead. [`InstanceSettings::width`] narrows or widens the
+//! stereo image before the instance is panned:
+//!
+//! ```no_run
+//! # use kira::{manager::AudioManager, instance::InstanceSettings, sound::Sound, PanLaw};
+//! #
+//! # let mut audio_manager = AudioManager::new(Default::default())?;
+//! # let mut sound = audio_manager.add_sound(Sound::from_file("loop.ogg", Default::default())?)?;
+//! let instance_handle = sound.play(
+//! 	InstanceSettings::new()
+//! 		.pan_law(PanLaw::MinusFourPointFiveDb)
+//! 		.balance()
+//! 		.panning(0.25)
+//! 		.width(0.5),
+//! )?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! ## Speed and time stretching
+//!
+//! The speed of an instance controls how fast its playback
+//! position moves. Normally, playing an instance faster also
+//! raises its pitch, and changing its pitch also changes its speed,
+//! just like speeding up a tape. If you enable
+//! [`InstanceSettings::time_stretch`], the audio is time-stretched
+//! instead, so the speed and pitch can be changed independently:
+//!
+//! ```no_run
+//! # use kira::{manager::AudioManager, instance::InstanceSettings, sound::Sound};
+//! #
+//! # let mut audio_manager = AudioManager::new(Default::default())?;
+//! # let mut sound = audio_manager.add_sound(Sound::from_file("loop.ogg", Default::default())?)?;
+//! // play the sound at half speed without lowering its pitch
+//! let instance_handle = sound.play(InstanceSettings::new().speed(0.5).time_stretch())?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Time stretching works by overlapping short grains of audio,
+//! so it can smear sharp transients.
+//!
+//! [`InstanceSettings::follow_tempo`] links the speed of an
+//! instance to the tempo of a metronome, so a loop written at
+//! one tempo stays in time as the metronome's tempo changes:
+//!
+//! ```no_run
+//! # use kira::{
+//! # 	manager::AudioManager, instance::InstanceSettings, metronome::MetronomeSettings,
+//! # 	arrangement::{Arrangement, LoopArrangementSettings}, sound::Sound, Tempo,
+//! # };
+//! #
+//! # let mut audio_manager = AudioManager::new(Default::default())?;
+//! # let sound = audio_manager.add_sound(Sound::from_file("loop.ogg", Default::default())?)?;
+//! # let mut arrangement = audio_manager.add_arrangement(Arrangement::new_loop(&sound, LoopArrangementSettings::default()))?;
+//! let metronome = audio_manager.add_metronome(MetronomeSettings::new().tempo(Tempo(140.0)))?;
+//! // the loop was written at 120 BPM, so it plays 7/6 as fast
+//! let instance_handle = arrangement.play(
+//! 	InstanceSettings::new()
+//! 		.follow_tempo(&metronome, Tempo(120.0))
+//! 		.time_stretch(),
+//! )?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! ## Granular playback
+//!
+//! Setting [`InstanceSettings::granular`] plays the audio as a
+//! stream of short, overlapping grains read from around a scan
+//! position, which is useful for ambient textures and engine
+//! sounds. All of the [`GranularSettings`] are [`Value`]s, so
+//! they can be controlled by parameters:
+//!
+//! ```no_run
+//! # use kira::{
+//! # 	manager::AudioManager,
+//! # 	instance::{GranularSettings, InstanceSettings},
+//! # 	parameter::ParameterSettings,
+//! # 	sound::Sound,
+//! # };
+//! #
+//! # let mut audio_manager = AudioManager::new(Default::default())?;
+//! # let mut sound = audio_manager.add_sound(Sound::from_file("engine.ogg", Default::default())?)?;
+//! let mut rpm = audio_manager.add_parameter(ParameterSettings::new())?;
+//! let instance_handle = sound.play(
+//! 	InstanceSettings::new().granular(
+//! 		GranularSettings::new()
+//! 			.grain_size(0.05)
+//! 			.density(40.0)
+//! 			.scan_position(&rpm)
+//! 			.position_jitter(0.02),
+//! 	),
+//! )?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Granular instances keep playing until they're stopped.
+//!
+//! ## Markers
+//!
+//! Sounds and arrangements can have named [`Marker`]s.
+//! Whenever the playback position of an instance passes
+//! a marker, whether it's playing forward or backward,
+//! the instance will emit an event, which you can receive
+//! using [`InstanceHandle::pop_marker`]:
+//!
+//! ```no_run
+//! # use std::error::Error;
+//! #
+//! # use kira::{manager::AudioManager, instance::InstanceSettings, sound::{Sound, SoundSettings}};
+//! #
+//! # let mut audio_manager = AudioManager::new(Default::default())?;
+//! let mut sound = audio_manager.add_sound(Sound::from_file(
+//! 	"footsteps.ogg",
+//! 	SoundSettings::new().marker(0.25, "left").marker(0.75, "right"),
+//! )?)?;
+//! let mut instance_handle = sound.play(InstanceSettings::default())?;
+//! while let Some(marker) = instance_handle.pop_marker() {
+//! 	println!("{}", marker.name);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! ## Events
+//!
+//! Instances also emit [`InstanceEvent`]s when they start
+//! playing, loop, finish, finish fading, or are stopped, which
+//! you can receive using [`InstanceHandle::pop_event`]. This
+//! lets you react to an instance finishing without checking
+//! its state every frame:
+//!
+//! ```no_run
+//! # use kira::{manager::AudioManager, instance::{InstanceEvent, InstanceSettings}, sound::Sound};
+//! #
+//! # let mut audio_manager = AudioManager::new(Default::default())?;
+//! # let mut sound = audio_manager.add_sound(Sound::from_file("explosion.ogg", Default::default())?)?;
+//! let mut instance_handle = sound.play(InstanceSettings::default())?;
+//! while let Some(event) = instance_handle.pop_event() {
+//! 	if event == InstanceEvent::Finished {
+//! 		println!("the explosion finished");
+//! 	}
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! ## Crossfading
+//!
+//! [`AudioManager::crossfade`](crate::manager::AudioManager::crossfade)
+//! fades out an instance while starting and fading in a new
+//! instance of another sound or arrangement, which is handy for
+//! switching between music tracks. The new instance can pick up
+//! from the old instance's playback position, and the crossfade
+//! can wait for a metronome interval:
+//!
+//! ```no_run
+//! # use kira::{
+//! # 	manager::AudioManager,
+//! # 	instance::{CrossfadeSettings, InstanceSettings, InstanceTiming},
+//! # 	metronome::MetronomeSettings,
+//! # 	sound::Sound,
+//! # };
+//! #
+//! # let mut audio_manager = AudioManager::new(Default::default())?;
+//! # let mut calm = audio_manager.add_sound(Sound::from_file("calm.ogg", Default::default())?)?;
+//! # let intense = audio_manager.add_sound(Sound::from_file("intense.ogg", Default::default())?)?;
+//! # let metronome = audio_manager.add_metronome(MetronomeSettings::new())?;
+//! let calm_instance = calm.play(InstanceSettings::default())?;
+//! let intense_instance = audio_manager.crossfade(
+//! 	calm_instance.id(),
+//! 	intense.id(),
+//! 	CrossfadeSettings::new()
+//! 		.duration(2.0)
+//! 		.match_position()
+//! 		.timing(InstanceTiming::new().quantize(&metronome, 4.0)),
+//! )?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
 
+mod granular;
 pub mod handle;
 mod settings;
+mod time_stretch;
+mod tweened_value;
 
 use atomic::Atomic;
+use flume::Sender;
+use granular::GranularPlayer;
 use handle::InstanceHandle;
 pub use settings::*;
 
@@ -68,15 +297,25 @@ use uuid::Uuid;
 
 use crate::{
 	frame::Frame,
-	mixer::TrackIndex,
+	metronome::{MetronomeId, Metronomes},
+	mixer::{
+		effect::{Effect, EffectId, EffectSettings},
+		effect_slot::EffectSlot,
+		TrackIndex,
+	},
+	parameter::tween::Tween,
 	parameter::{Parameter, Parameters},
 	playable::{PlayableId, Playables},
 	sequence::SequenceInstanceId,
+	spatial::{BinauralRenderer, DistanceFilter, EmitterId, SpatialScene},
 	util::generate_uuid,
-	value::CachedValue,
 	value::Value,
+	Marker, PanLaw, Tempo,
 };
+use indexmap::IndexMap;
 use std::sync::{atomic::Ordering, Arc};
+use time_stretch::TimeStretcher;
+use tweened_value::TweenedValue;
 
 /// A unique identifier for an instance.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -106,6 +345,8 @@ impl From<&InstanceHandle> for InstanceId {
 /// The playback state of the instance.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum InstanceState {
+	/// The instance is waiting to start playing.
+	Pending,
 	/// The instance is playing.
 	Playing,
 	/// The instance is paused.
@@ -126,21 +367,107 @@ pub enum InstanceState {
 	Stopping,
 }
 
-#[derive(Debug, Clone)]
+/// Something that happened to an instance.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum InstanceEvent {
+	/// The instance started playing on the audio thread.
+	Started,
+	/// The instance reached the end of the audio and
+	/// jumped back to its loop start point.
+	Looped,
+	/// The instance reached the end of the audio
+	/// (or the beginning, if it's playing backward)
+	/// and stopped.
+	Finished,
+	/// A fade-in, or a fade-out caused by pausing, resuming,
+	/// or stopping the instance, finished.
+	FadeFinished,
+	/// The instance was stopped before reaching the end.
+	///
+	/// This is only emitted for instances that have emitted
+	/// [`Started`](InstanceEvent::Started). If the instance couldn't
+	/// be started because its sound or arrangement was cooling
+	/// down or at its instance limit, it emits no events, and its
+	/// state is set to [`InstanceState::Stopped`].
+	Stopped,
+	/// The instance is being stopped to make room for a new
+	/// instance.
+	///
+	/// If the instance was stopped because its sound or arrangement
+	/// reached its instance limit, it fades out after this event, so
+	/// it's followed by [`FadeFinished`](InstanceEvent::FadeFinished)
+	/// and then [`Stopped`](InstanceEvent::Stopped). If it was stopped
+	/// because the audio manager reached its
+	/// [`num_instances`](crate::manager::AudioManagerSettings::num_instances)
+	/// limit, it's removed right away and followed by
+	/// [`Stopped`](InstanceEvent::Stopped) without a fade.
+	Stolen,
+}
+
+/// A send to another track that's part of a playing instance.
+#[derive(Debug, Copy, Clone)]
+struct ActiveSend {
+	track: TrackIndex,
+	volume: TweenedValue,
+	pre_volume: bool,
+}
+
+/// The output of an instance for a single frame.
+pub(crate) struct InstanceOutput {
+	/// The audio before the instance's volume is applied.
+	pub pre_volume: Frame,
+	/// The audio after the instance's volume is applied.
+	pub post_volume: Frame,
+}
+
+#[derive(Debug)]
 pub(crate) struct Instance {
+	id: InstanceId,
 	playable_id: PlayableId,
 	duration: f64,
 	sequence_id: Option<SequenceInstanceId>,
 	track_index: TrackIndex,
-	volume: CachedValue<f64>,
-	pitch: CachedValue<f64>,
-	panning: CachedValue<f64>,
+	volume: TweenedValue,
+	pitch: TweenedValue,
+	panning: TweenedValue,
+	pan_law: PanLaw,
+	balance: bool,
+	width: TweenedValue,
+	speed: TweenedValue,
+	follow_tempo: Option<(MetronomeId, Tempo)>,
+	/// The metronome's tempo divided by the tempo the
+	/// audio was written at.
+	tempo_ratio: f64,
+	time_stretcher: Option<TimeStretcher>,
+	granular_player: Option<GranularPlayer>,
+	emitter: Option<EmitterId>,
+	distance_filter: DistanceFilter,
+	/// Lent to the instance by the backend while it's
+	/// rendered binaurally.
+	binaural_renderer: Option<BinauralRenderer>,
+	doppler: f64,
 	reverse: bool,
 	loop_start: Option<f64>,
+	loop_end: Option<f64>,
+	loops_remaining: Option<usize>,
+	loop_released: bool,
+	loop_crossfade: Option<f64>,
+	wrapped_backward: bool,
+	loop_seam_blend: Option<(f64, f32)>,
+	start_timing: InstanceTiming,
 	state: InstanceState,
 	public_state: Arc<Atomic<InstanceState>>,
 	position: f64,
+	public_position: Arc<Atomic<f64>>,
 	fade_volume: Parameter,
+	effect_slots: IndexMap<EffectId, EffectSlot>,
+	sends: Vec<ActiveSend>,
+	marker_event_sender: Option<Sender<usize>>,
+	event_sender: Option<Sender<InstanceEvent>>,
 }
 
 impl Instance {
@@ -149,6 +476,8 @@ impl Instance {
 		duration: f64,
 		sequence_id: Option<SequenceInstanceId>,
 		settings: InternalInstanceSettings,
+		marker_event_sender: Option<Sender<usize>>,
+		event_sender: Option<Sender<InstanceEvent>>,
 	) -> Self {
 		let mut fade_volume;
 		if let Some(tween) = settings.fade_in_tween {
@@ -157,23 +486,70 @@ impl Instance {
 		} else {
 			fade_volume = Parameter::new(1.0);
 		}
+		let state = if settings.start_timing.is_immediate() {
+			InstanceState::Playing
+		} else {
+			InstanceState::Pending
+		};
 		Self {
+			id: settings.id,
 			playable_id: playable,
 			duration,
 			sequence_id,
 			track_index: settings.track,
-			volume: CachedValue::new(settings.volume, 1.0),
-			pitch: CachedValue::new(settings.pitch, 1.0),
-			panning: CachedValue::new(settings.panning, 0.5),
+			volume: TweenedValue::new(settings.volume, 1.0),
+			pitch: TweenedValue::new(settings.pitch, 1.0),
+			panning: TweenedValue::new(settings.panning, 0.5),
+			pan_law: settings.pan_law,
+			balance: settings.balance,
+			width: TweenedValue::new(settings.width, 1.0),
+			speed: TweenedValue::new(settings.speed, 1.0),
+			follow_tempo: settings.follow_tempo,
+			tempo_ratio: 1.0,
+			time_stretcher: if settings.time_stretch {
+				Some(TimeStretcher::new())
+			} else {
+				None
+			},
+			granular_player: settings.granular.map(GranularPlayer::new),
+			emitter: settings.emitter,
+			distance_filter: DistanceFilter::new(),
+			binaural_renderer: None,
+			doppler: 1.0,
 			reverse: settings.reverse,
 			loop_start: settings.loop_start,
-			state: InstanceState::Playing,
-			public_state: Arc::new(Atomic::new(InstanceState::Playing)),
+			loop_end: settings.loop_end,
+			loops_remaining: settings.loop_count,
+			loop_released: false,
+			loop_crossfade: settings.loop_crossfade,
+			wrapped_backward: false,
+			loop_seam_blend: None,
+			start_timing: settings.start_timing,
+			state,
+			public_state: Arc::new(Atomic::new(state)),
 			position: settings.start_position,
+			public_position: Arc::new(Atomic::new(settings.start_position)),
 			fade_volume,
+			effect_slots: IndexMap::new(),
+			sends: settings
+				.sends
+				.iter()
+				.flatten()
+				.map(|send| ActiveSend {
+					track: send.track,
+					volume: TweenedValue::new(send.volume, 1.0),
+					pre_volume: send.pre_volume,
+				})
+				.collect(),
+			marker_event_sender,
+			event_sender,
 		}
 	}
 
+	pub fn id(&self) -> InstanceId {
+		self.id
+	}
+
 	pub fn playable_id(&self) -> PlayableId {
 		self.playable_id
 	}
@@ -186,6 +562,10 @@ impl Instance {
 		self.sequence_id
 	}
 
+	pub fn position(&self) -> f64 {
+		self.position
+	}
+
 	pub fn effective_volume(&self) -> f64 {
 		self.volume.value() * self.fade_volume.value()
 	}
@@ -194,8 +574,17 @@ impl Instance {
 		self.public_state.clone()
 	}
 
+	pub fn public_position(&self) -> Arc<Atomic<f64>> {
+		self.public_position.clone()
+	}
+
+	pub fn start_timing(&self) -> InstanceTiming {
+		self.start_timing
+	}
+
 	pub fn playing(&self) -> bool {
 		match self.state {
+			InstanceState::Pending => false,
 			InstanceState::Playing => true,
 			InstanceState::Paused(_) => false,
 			InstanceState::Stopped => false,
@@ -208,24 +597,257 @@ impl Instance {
 		self.state == InstanceState::Stopped
 	}
 
-	pub fn set_volume(&mut self, volume: Value<f64>) {
-		self.volume.set(volume);
+	/// Returns `true` if the instance is attached to a binaural
+	/// emitter and the listener has an HRIR set.
+	pub fn renders_binaurally(&self, spatial_scene: &SpatialScene) -> bool {
+		self.emitter
+			.and_then(|emitter| spatial_scene.emitter_output(emitter))
+			.map_or(false, |output| output.hrir_weights.is_some())
+	}
+
+	pub fn has_binaural_renderer(&self) -> bool {
+		self.binaural_renderer.is_some()
+	}
+
+	pub fn set_binaural_renderer(&mut self, renderer: BinauralRenderer) {
+		self.binaural_renderer = Some(renderer);
+	}
+
+	pub fn take_binaural_renderer(&mut self) -> Option<BinauralRenderer> {
+		self.binaural_renderer.take()
 	}
 
-	pub fn set_pitch(&mut self, pitch: Value<f64>) {
-		self.pitch.set(pitch);
+	/// Returns `true` if the instance is stopped or
+	/// fading out before stopping.
+	pub fn stopping(&self) -> bool {
+		matches!(self.state, InstanceState::Stopping | InstanceState::Stopped)
 	}
 
-	pub fn set_panning(&mut self, panning: Value<f64>) {
-		self.panning.set(panning);
+	pub fn set_volume(&mut self, volume: Value<f64>, tween: Option<Tween>) {
+		self.volume.set(volume, tween);
+	}
+
+	pub fn set_pitch(&mut self, pitch: Value<f64>, tween: Option<Tween>) {
+		self.pitch.set(pitch, tween);
+	}
+
+	pub fn set_panning(&mut self, panning: Value<f64>, tween: Option<Tween>) {
+		self.panning.set(panning, tween);
+	}
+
+	pub fn set_width(&mut self, width: Value<f64>, tween: Option<Tween>) {
+		self.width.set(width, tween);
+	}
+
+	pub fn set_speed(&mut self, speed: Value<f64>, tween: Option<Tween>) {
+		self.speed.set(speed, tween);
+	}
+
+	/// Applies the instance's stereo width and pans
+	/// a frame using its pan law.
+	fn pan(&self, frame: Frame, panning: f32) -> Frame {
+		let frame = frame.with_width(self.width.value() as f32);
+		if self.balance {
+			frame.balanced(panning, self.pan_law)
+		} else {
+			frame.panned_with_law(panning, self.pan_law)
+		}
+	}
+
+	/// Returns the pitch of the instance, including the
+	/// Doppler effect from its emitter.
+	fn effective_pitch(&self) -> f64 {
+		self.pitch.value() * self.doppler
+	}
+
+	/// Returns how fast the playback position moves, which is
+	/// negative when playing backward.
+	fn playback_rate(&self) -> f64 {
+		let mut rate = self.speed.value() * self.tempo_ratio;
+		if self.time_stretcher.is_none() {
+			rate *= self.effective_pitch();
+		}
+		if self.reverse {
+			rate *= -1.0;
+		}
+		rate
+	}
+
+	fn set_position(&mut self, position: f64) {
+		self.position = position;
+		self.public_position.store(position, Ordering::Relaxed);
+	}
+
+	pub fn set_send_volume(&mut self, track: TrackIndex, volume: Value<f64>, tween: Option<Tween>) {
+		for send in &mut self.sends {
+			if send.track == track {
+				send.volume.set(volume, tween);
+			}
+		}
+	}
+
+	/// Calls `f` with the track and volume of each send.
+	pub fn for_each_send(&self, mut f: impl FnMut(TrackIndex, f32, bool)) {
+		for send in &self.sends {
+			f(send.track, send.volume.value() as f32, send.pre_volume);
+		}
 	}
 
 	pub fn seek(&mut self, offset: f64) {
-		self.position += offset;
+		self.set_position(self.position + offset);
 	}
 
 	pub fn seek_to(&mut self, position: f64) {
-		self.position = position;
+		self.set_position(position);
+	}
+
+	/// Seeks to a position, wrapping it around to the beginning
+	/// if it's past the end of the sound or arrangement.
+	pub fn seek_to_wrapped(&mut self, position: f64) {
+		if self.duration > 0.0 {
+			self.set_position(position.rem_euclid(self.duration));
+		} else {
+			self.set_position(0.0);
+		}
+	}
+
+	/// Updates the duration of the playable this instance is
+	/// playing, keeping the playback position within the
+	/// new duration.
+	fn set_duration(&mut self, duration: f64) {
+		self.duration = duration;
+		self.position = self.position.max(0.0).min(duration);
+		// a loop start point at or past the end would keep the
+		// instance from ever leaving the end of the audio
+		if let Some(loop_start) = self.loop_start {
+			self.loop_start = if duration <= 0.0 {
+				None
+			} else if loop_start >= duration {
+				Some(0.0)
+			} else {
+				Some(loop_start)
+			};
+		}
+	}
+
+	pub fn add_effect(&mut self, effect: Box<dyn Effect>, settings: EffectSettings) {
+		let id = settings.id;
+		self.effect_slots
+			.insert(id, EffectSlot::new(effect, settings));
+	}
+
+	pub fn effect_mut(&mut self, id: EffectId) -> Option<&mut EffectSlot> {
+		self.effect_slots.get_mut(&id)
+	}
+
+	pub fn remove_effect(&mut self, id: EffectId) -> Option<EffectSlot> {
+		self.effect_slots.shift_remove(&id)
+	}
+
+	/// Starts playing an instance that was waiting
+	/// for its start time.
+	pub fn start(&mut self) {
+		if self.state == InstanceState::Pending {
+			self.set_state(InstanceState::Playing);
+		}
+	}
+
+	pub fn emit_event(&self, event: InstanceEvent) {
+		if let Some(sender) = &self.event_sender {
+			sender.try_send(event).ok();
+		}
+	}
+
+	/// Returns the start and end points of the section of
+	/// audio the instance loops, if it has one.
+	fn loop_region(&self) -> Option<(f64, f64)> {
+		let loop_start = self.loop_start?;
+		let loop_end = self
+			.loop_end
+			.map(|loop_end| loop_end.min(self.duration))
+			.unwrap_or(self.duration);
+		if loop_end > loop_start {
+			Some((loop_start, loop_end))
+		} else {
+			None
+		}
+	}
+
+	/// Returns the start and end points of the section before the
+	/// loop end point that's crossfaded with the section before
+	/// the loop start point, if the instance has one.
+	///
+	/// The crossfade can't be longer than the loop region or
+	/// the audio before the loop start point.
+	fn loop_crossfade_region(&self) -> Option<(f64, f64)> {
+		let (loop_start, loop_end) = self.loop_region()?;
+		let length = self
+			.loop_crossfade?
+			.min(loop_end - loop_start)
+			.min(loop_start);
+		if length > 0.0 {
+			Some((loop_end - length, loop_end))
+		} else {
+			None
+		}
+	}
+
+	/// Decides how much of the audio before the loop start point
+	/// should be mixed into the audio before the loop end point.
+	///
+	/// When playing forward, the crossfade happens right before
+	/// the instance jumps back, so it's skipped on the last pass
+	/// through the loop. When playing backward, it happens right
+	/// after the instance wraps around to the loop end point.
+	fn update_loop_seam_blend(&mut self, backward: bool) {
+		self.loop_seam_blend = None;
+		let (start, end) = match self.loop_crossfade_region() {
+			Some(region) => region,
+			None => {
+				self.wrapped_backward = false;
+				return;
+			}
+		};
+		if self.position < start || self.position > end {
+			self.wrapped_backward = false;
+			return;
+		}
+		let crossfading = if backward {
+			self.wrapped_backward
+		} else {
+			self.looping()
+		};
+		if crossfading {
+			let (loop_start, loop_end) = self.loop_region().unwrap();
+			let amount = (self.position - start) / (end - start);
+			self.loop_seam_blend = Some((self.position - (loop_end - loop_start), amount as f32));
+		}
+	}
+
+	/// Returns `true` if the instance should jump back when
+	/// it reaches the end of its loop region.
+	fn looping(&self) -> bool {
+		!self.loop_released && self.loops_remaining != Some(0)
+	}
+
+	fn complete_loop(&mut self) {
+		if let Some(loops_remaining) = &mut self.loops_remaining {
+			*loops_remaining -= 1;
+		}
+		self.emit_event(InstanceEvent::Looped);
+	}
+
+	/// Stops the instance from looping, letting the current
+	/// pass through the loop region finish and then playing
+	/// the rest of the audio.
+	pub fn release_loop(&mut self) {
+		self.loop_released = true;
+	}
+
+	/// Marks an instance that was never started as stopped
+	/// without emitting any events.
+	pub(crate) fn reject(&mut self) {
+		self.set_state(InstanceState::Stopped);
 	}
 
 	fn set_state(&mut self, state: InstanceState) {
@@ -256,61 +878,522 @@ impl Instance {
 	}
 
 	pub fn stop(&mut self, settings: StopInstanceSettings) {
-		self.set_state(if settings.fade_tween.is_some() {
-			InstanceState::Stopping
+		if settings.fade_tween.is_some() {
+			self.set_state(InstanceState::Stopping);
 		} else {
-			InstanceState::Stopped
-		});
+			if self.state != InstanceState::Stopped {
+				self.emit_event(InstanceEvent::Stopped);
+			}
+			self.set_state(InstanceState::Stopped);
+		}
 		self.fade_volume.set(0.0, settings.fade_tween);
 	}
 
-	pub fn update(&mut self, dt: f64, parameters: &Parameters) {
-		if self.playing() {
-			self.volume.update(parameters);
-			self.pitch.update(parameters);
-			self.panning.update(parameters);
-			let mut pitch = self.pitch.value();
-			if self.reverse {
-				pitch *= -1.0;
-			}
-			self.position += pitch * dt;
-			if pitch < 0.0 {
-				if let Some(loop_start) = self.loop_start {
-					while self.position < loop_start {
-						self.position += self.duration - loop_start;
+	/// Emits events for each marker the playback position
+	/// has passed when moving from `from` to `to`.
+	///
+	/// When playing forward, markers at `from` are included and
+	/// markers at `to` are not. When playing backward, it's the
+	/// other way around.
+	fn emit_passed_markers(&self, markers: &[Marker], from: f64, to: f64) {
+		if let Some(sender) = &self.marker_event_sender {
+			if from <= to {
+				for (index, marker) in markers.iter().enumerate() {
+					if marker.position >= from && marker.position < to {
+						sender.try_send(index).ok();
 					}
-				} else if self.position < 0.0 {
-					self.set_state(InstanceState::Stopped);
 				}
 			} else {
-				if let Some(loop_start) = self.loop_start {
-					while self.position > self.duration {
-						self.position -= self.duration - loop_start;
+				for (index, marker) in markers.iter().enumerate().rev() {
+					if marker.position > to && marker.position <= from {
+						sender.try_send(index).ok();
 					}
-				} else if self.position > self.duration {
-					self.set_state(InstanceState::Stopped);
 				}
 			}
 		}
+	}
+
+	/// Moves the playback position forward (or backward), handling
+	/// loops and reaching the end of the audio.
+	fn update_position(&mut self, dt: f64, markers: &[Marker]) {
+		let pitch = self.playback_rate();
+		let mut previous_position = self.position;
+		self.position += pitch * dt;
+		if let Some((loop_start, loop_end)) = self.loop_region() {
+			let loop_length = loop_end - loop_start;
+			if pitch < 0.0 && previous_position >= loop_start {
+				while self.looping() && self.position < loop_start {
+					self.emit_passed_markers(markers, previous_position, loop_start);
+					self.position += loop_length;
+					previous_position = loop_end;
+					self.wrapped_backward = true;
+					self.complete_loop();
+				}
+			} else if pitch >= 0.0 && previous_position <= loop_end {
+				while self.looping() && self.position > loop_end {
+					self.emit_passed_markers(markers, previous_position, loop_end);
+					self.position -= loop_length;
+					previous_position = loop_start;
+					self.complete_loop();
+				}
+			}
+		}
+		// only stop when the position moves past the end it's
+		// heading toward, so instances can start at a negative
+		// position (or past the end when playing backward)
+		let finished = if pitch < 0.0 {
+			self.position < 0.0
+		} else {
+			self.position > self.duration
+		};
+		if finished {
+			self.set_state(InstanceState::Stopped);
+			self.emit_event(InstanceEvent::Finished);
+		}
+		self.update_loop_seam_blend(pitch < 0.0);
+		self.emit_passed_markers(markers, previous_position, self.position);
+		self.public_position.store(self.position, Ordering::Relaxed);
+	}
+
+	/// Updates the speed multiplier for instances that
+	/// follow a metronome's tempo.
+	pub fn update_tempo_ratio(&mut self, metronomes: &Metronomes) {
+		if let Some((metronome, tempo)) = self.follow_tempo {
+			if let Some(metronome) = metronomes.get(metronome) {
+				if tempo.0 > 0.0 {
+					self.tempo_ratio = metronome.tempo().0 / tempo.0;
+				}
+			}
+		}
+	}
+
+	pub fn update(
+		&mut self,
+		dt: f64,
+		playables: &Playables,
+		parameters: &Parameters,
+		spatial_scene: &SpatialScene,
+	) {
+		if self.playing() {
+			self.volume.update(dt, parameters);
+			self.pitch.update(dt, parameters);
+			self.panning.update(dt, parameters);
+			self.width.update(dt, parameters);
+			self.speed.update(dt, parameters);
+			self.doppler = self
+				.emitter
+				.and_then(|emitter| spatial_scene.emitter_output(emitter))
+				.map_or(1.0, |spatial_output| spatial_output.doppler);
+			for send in &mut self.sends {
+				send.volume.update(dt, parameters);
+			}
+			let playable = playables.playable(self.playable_id);
+			if let Some(playable) = playable {
+				// the playable's duration changes if a sound is reloaded
+				if playable.duration() != self.duration {
+					self.set_duration(playable.duration());
+				}
+			}
+			if let Some(granular_player) = &mut self.granular_player {
+				granular_player.update(parameters);
+				self.position = granular_player.scan_position().max(0.0).min(self.duration);
+				self.public_position.store(self.position, Ordering::Relaxed);
+			} else {
+				let markers = playable.map(|playable| playable.markers()).unwrap_or(&[]);
+				self.update_position(dt, markers);
+			}
+		}
 		let finished_fading = self.fade_volume.update(dt);
 		if finished_fading {
+			self.emit_event(InstanceEvent::FadeFinished);
 			match self.state {
 				InstanceState::Pausing(position) => {
 					self.set_state(InstanceState::Paused(position));
 				}
 				InstanceState::Stopping => {
 					self.set_state(InstanceState::Stopped);
+					self.emit_event(InstanceEvent::Stopped);
 				}
 				_ => {}
 			}
 		}
 	}
 
-	pub fn get_sample(&self, playables: &Playables) -> Frame {
-		let mut out = playables
-			.frame_at_position(self.playable_id, self.position)
-			.unwrap_or(Frame::from_mono(0.0));
-		out = out.panned(self.panning.value() as f32);
-		out * (self.effective_volume() as f32)
+	fn get_time_stretched_frame(&mut self, dt: f64, playables: &Playables) -> Frame {
+		let playable_id = self.playable_id;
+		let duration = self.duration;
+		// grains that run past the end of the loop region
+		// continue from the other end
+		let loop_region = self.loop_region().filter(|_| self.looping());
+		let rate = self.effective_pitch() * self.playback_rate().signum();
+		let position = self.position;
+		let time_stretcher = match &mut self.time_stretcher {
+			Some(time_stretcher) => time_stretcher,
+			None => return Frame::from_mono(0.0),
+		};
+		time_stretcher.process(dt, position, rate, |mut position| {
+			if let Some((loop_start, loop_end)) = loop_region {
+				let loop_length = loop_end - loop_start;
+				if rate >= 0.0 && position > loop_end {
+					position -= loop_length * ((position - loop_start) / loop_length).floor();
+				} else if rate < 0.0 && position < loop_start {
+					position += loop_length * ((loop_end - position) / loop_length).floor();
+				}
+			}
+			if position < 0.0 || position > duration {
+				return Frame::from_mono(0.0);
+			}
+			playables
+				.frame_at_position(playable_id, position)
+				.unwrap_or(Frame::from_mono(0.0))
+		})
+	}
+
+	fn get_granular_frame(&mut self, dt: f64, playables: &Playables) -> Frame {
+		let playable_id = self.playable_id;
+		let duration = self.duration;
+		let rate = self.effective_pitch() * if self.reverse { -1.0 } else { 1.0 };
+		let granular_player = match &mut self.granular_player {
+			Some(granular_player) => granular_player,
+			None => return Frame::from_mono(0.0),
+		};
+		granular_player.process(dt, rate, |position| {
+			if position < 0.0 || position > duration {
+				return Frame::from_mono(0.0);
+			}
+			playables
+				.frame_at_position(playable_id, position)
+				.unwrap_or(Frame::from_mono(0.0))
+		})
+	}
+
+	pub fn get_sample(
+		&mut self,
+		dt: f64,
+		playables: &Playables,
+		parameters: &Parameters,
+		spatial_scene: &SpatialScene,
+	) -> InstanceOutput {
+		let mut out = if self.granular_player.is_some() {
+			self.get_granular_frame(dt, playables)
+		} else if self.time_stretcher.is_some() {
+			self.get_time_stretched_frame(dt, playables)
+		} else {
+			playables
+				.frame_at_position(self.playable_id, self.position)
+				.unwrap_or(Frame::from_mono(0.0))
+		};
+		if let Some((position, amount)) = self.loop_seam_blend {
+			// equal-power crossfade, since the two sections of
+			// audio usually aren't correlated
+			let other = playables
+				.frame_at_position(self.playable_id, position)
+				.unwrap_or(Frame::from_mono(0.0));
+			let angle = amount * std::f32::consts::FRAC_PI_2;
+			out = out * angle.cos() + other * angle.sin();
+		}
+		match self
+			.emitter
+			.and_then(|emitter| spatial_scene.emitter_output(emitter))
+		{
+			Some(spatial_output) => {
+				if let Some(cutoff) = spatial_output.cutoff {
+					out = self.distance_filter.process(dt, out, cutoff);
+				}
+				let binaural = spatial_output.hrir_weights.and_then(|weights| {
+					let hrir_set = spatial_scene.hrir_set()?;
+					let renderer = self.binaural_renderer.as_mut()?;
+					Some(renderer.process(dt, out, hrir_set, weights))
+				});
+				out = binaural.unwrap_or_else(|| self.pan(out, spatial_output.panning as f32))
+					* spatial_output.volume as f32;
+			}
+			None => {
+				out = self.pan(out, self.panning.value() as f32);
+			}
+		}
+		for (_, effect_slot) in &mut self.effect_slots {
+			out = effect_slot.process(dt, out, parameters);
+		}
+		out *= self.fade_volume.value() as f32;
+		InstanceOutput {
+			pre_volume: out,
+			post_volume: out * (self.volume.value() as f32),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::f64::consts::PI;
+
+	use flume::Receiver;
+
+	use crate::{
+		command::ResourceCommand,
+		mixer::TrackIndex,
+		parameter::Parameters,
+		playable::Playables,
+		sound::{Sound, SoundId, SoundSettings},
+		spatial::SpatialScene,
+		Frame, Marker,
+	};
+
+	use super::{Instance, InstanceEvent, InstanceSettings, PlayableId};
+
+	const DURATION: f64 = 10.0;
+
+	fn instance(
+		settings: InstanceSettings,
+	) -> (Instance, Receiver<usize>, Receiver<InstanceEvent>) {
+		let (marker_event_sender, marker_event_receiver) = flume::unbounded();
+		let (event_sender, event_receiver) = flume::unbounded();
+		let instance = Instance::new(
+			PlayableId::Sound(SoundId::new()),
+			DURATION,
+			None,
+			settings.into_internal(DURATION, None, TrackIndex::Main),
+			Some(marker_event_sender),
+			Some(event_sender),
+		);
+		(instance, marker_event_receiver, event_receiver)
+	}
+
+	fn markers(positions: &[f64]) -> Vec<Marker> {
+		positions
+			.iter()
+			.map(|&position| Marker::new(position, ""))
+			.collect()
+	}
+
+	#[test]
+	fn markers_are_passed_in_order() {
+		let markers = markers(&[0.0, 1.0, 2.5, 3.0]);
+		let (mut instance, marker_events, _) = instance(InstanceSettings::new());
+		for _ in 0..3 {
+			instance.update_position(1.0, &markers);
+		}
+		// markers at the new position haven't been passed yet
+		assert_eq!(marker_events.drain().collect::<Vec<_>>(), [0, 1, 2]);
+		instance.update_position(1.0, &markers);
+		assert_eq!(marker_events.drain().collect::<Vec<_>>(), [3]);
+	}
+
+	#[test]
+	fn markers_are_passed_in_reverse() {
+		let markers = markers(&[1.0, 2.5, 4.0]);
+		let (mut instance, marker_events, _) =
+			instance(InstanceSettings::new().reverse().start_position(6.0));
+		assert_eq!(instance.position, 4.0);
+		for _ in 0..3 {
+			instance.update_position(1.0, &markers);
+		}
+		// when playing backward, markers at the previous position
+		// are passed and markers at the new position aren't
+		assert_eq!(marker_events.drain().collect::<Vec<_>>(), [2, 1]);
+		instance.update_position(1.0, &markers);
+		assert_eq!(marker_events.drain().collect::<Vec<_>>(), [0]);
+	}
+
+	#[test]
+	fn markers_are_passed_across_loop_wraps() {
+		let markers = markers(&[0.5, 1.5, 2.5]);
+		let (mut instance, marker_events, _) =
+			instance(InstanceSettings::new().loop_start(1.0).loop_end(3.0));
+		for _ in 0..6 {
+			instance.update_position(1.0, &markers);
+		}
+		assert_eq!(instance.position, 2.0);
+		assert_eq!(
+			marker_events.drain().collect::<Vec<_>>(),
+			[0, 1, 2, 1, 2, 1]
+		);
+	}
+
+	#[test]
+	fn markers_are_passed_across_reverse_loop_wraps() {
+		let markers = markers(&[0.5, 1.5, 2.5]);
+		let (mut instance, marker_events, _) = instance(
+			InstanceSettings::new()
+				.reverse()
+				.start_position(DURATION - 3.0)
+				.loop_start(1.0)
+				.loop_end(3.0),
+		);
+		for _ in 0..5 {
+			instance.update_position(1.0, &markers);
+		}
+		assert_eq!(instance.position, 2.0);
+		assert_eq!(marker_events.drain().collect::<Vec<_>>(), [2, 1, 2, 1, 2]);
+	}
+
+	/// Plays an instance until it finishes, returning the playback
+	/// position after each step.
+	fn play_to_end(instance: &mut Instance, dt: f64) -> Vec<f64> {
+		let mut positions = vec![];
+		while instance.playing() {
+			instance.update_position(dt, &[]);
+			positions.push(instance.position);
+			assert!(positions.len() < 1000, "the instance never finished");
+		}
+		positions
+	}
+
+	#[test]
+	fn finite_loop_count() {
+		let (mut instance, _, events) = instance(
+			InstanceSettings::new()
+				.loop_start(1.0)
+				.loop_end(3.0)
+				.loop_count(2),
+		);
+		let positions = play_to_end(&mut instance, 0.5);
+		assert_eq!(
+			events.drain().collect::<Vec<_>>(),
+			[
+				InstanceEvent::Looped,
+				InstanceEvent::Looped,
+				InstanceEvent::Finished
+			]
+		);
+		// once the loops are used up, the instance plays past
+		// the loop end point
+		assert!(positions.iter().any(|&position| position > 3.0));
+	}
+
+	#[test]
+	fn releasing_a_loop_finishes_the_current_pass() {
+		let (mut instance, _, events) =
+			instance(InstanceSettings::new().loop_start(1.0).loop_end(3.0));
+		while events.try_recv() != Ok(InstanceEvent::Looped) {
+			instance.update_position(0.5, &[]);
+		}
+		assert_eq!(instance.position, 1.5);
+		instance.update_position(0.5, &[]);
+		instance.release_loop();
+		for _ in 0..3 {
+			instance.update_position(0.5, &[]);
+		}
+		assert_eq!(instance.position, 3.5);
+		play_to_end(&mut instance, 0.5);
+		assert_eq!(
+			events.drain().collect::<Vec<_>>(),
+			[InstanceEvent::Finished]
+		);
+	}
+
+	#[test]
+	fn reverse_playback_with_loop_end() {
+		let (mut instance, _, events) = instance(
+			InstanceSettings::new()
+				.reverse()
+				.loop_start(1.0)
+				.loop_end(3.0)
+				.loop_count(1),
+		);
+		assert_eq!(instance.position, DURATION);
+		let positions = play_to_end(&mut instance, 0.5);
+		assert_eq!(
+			events.drain().collect::<Vec<_>>(),
+			[InstanceEvent::Looped, InstanceEvent::Finished]
+		);
+		// the instance wraps around to the loop end point,
+		// not the end of the audio
+		let wrap = positions
+			.windows(2)
+			.position(|pair| pair[1] > pair[0])
+			.unwrap();
+		assert_eq!(positions[wrap + 1], 2.5);
+		assert!(positions[wrap + 1..]
+			.iter()
+			.all(|&position| position <= 3.0));
+	}
+
+	#[test]
+	fn loop_crossfade_is_clamped() {
+		// to the length of the loop region
+		let (mut short_loop, _, _) = instance(
+			InstanceSettings::new()
+				.loop_start(1.0)
+				.loop_end(1.5)
+				.loop_crossfade(2.0),
+		);
+		assert_eq!(short_loop.loop_crossfade_region(), Some((1.0, 1.5)));
+		short_loop.position = 1.0;
+		short_loop.update_loop_seam_blend(false);
+		assert_eq!(short_loop.loop_seam_blend, Some((0.5, 0.0)));
+		short_loop.position = 1.5;
+		short_loop.update_loop_seam_blend(false);
+		assert_eq!(short_loop.loop_seam_blend, Some((1.0, 1.0)));
+		// to the audio before the loop start point
+		let (late_loop, _, _) = instance(
+			InstanceSettings::new()
+				.loop_start(0.25)
+				.loop_end(3.0)
+				.loop_crossfade(1.0),
+		);
+		assert_eq!(late_loop.loop_crossfade_region(), Some((2.75, 3.0)));
+	}
+
+	/// Plays a sine wave whose loop region isn't a whole number
+	/// of cycles and returns the largest difference between
+	/// consecutive output samples.
+	fn largest_step_in_looped_sine(loop_crossfade: Option<f64>, reverse: bool) -> f32 {
+		const SAMPLE_RATE: u32 = 1000;
+		let frames = (0..SAMPLE_RATE * 2)
+			.map(
+				|i| Frame::from_mono((2.0 * PI * 3.0 * i as f64 / SAMPLE_RATE as f64).sin() as f32),
+			)
+			.collect();
+		let sound = Sound::from_frames(SAMPLE_RATE, frames, SoundSettings::new());
+		let (playable_id, duration) = (PlayableId::Sound(sound.id()), sound.duration());
+		let (mut unloader, _) = flume::unbounded();
+		let mut playables = Playables::new(1, 0);
+		playables.run_command(ResourceCommand::AddSound(sound), &mut unloader);
+		let parameters = Parameters::new(0);
+		let spatial_scene = SpatialScene::new(0);
+		let mut settings = InstanceSettings::new().loop_start(0.5).loop_end(1.37);
+		if let Some(loop_crossfade) = loop_crossfade {
+			settings = settings.loop_crossfade(loop_crossfade);
+		}
+		if reverse {
+			settings = settings.reverse();
+		}
+		let mut instance = Instance::new(
+			playable_id,
+			duration,
+			None,
+			settings.into_internal(duration, None, TrackIndex::Main),
+			None,
+			None,
+		);
+		let dt = 1.0 / SAMPLE_RATE as f64;
+		let mut previous: Option<f32> = None;
+		let mut largest_step = 0.0f32;
+		for _ in 0..SAMPLE_RATE * 5 {
+			let sample = instance
+				.get_sample(dt, &playables, &parameters, &spatial_scene)
+				.post_volume
+				.left;
+			if let Some(previous) = previous {
+				largest_step = largest_step.max((sample - previous).abs());
+			}
+			previous = Some(sample);
+			instance.update(dt, &playables, &parameters, &spatial_scene);
+		}
+		largest_step
+	}
+
+	#[test]
+	fn loop_crossfade_is_continuous_at_the_wrap() {
+		// without a crossfade, the instance jumps to a different
+		// point in the sine wave's cycle
+		for &reverse in &[false, true] {
+			assert!(largest_step_in_looped_sine(None, reverse) > 0.3);
+			let largest_step = largest_step_in_looped_sine(Some(0.2), reverse);
+			// the sine wave itself changes by up to about 0.013
+			// per sample after panning
+			assert!(largest_step < 0.03, "{}", largest_step);
+		}
 	}
 }
@@ -1,9 +1,13 @@
 use crate::{
+	metronome::MetronomeId,
 	mixer::{SubTrackId, TrackIndex},
 	parameter::tween::{EaseDirection, Easing, Tween},
-	Value,
+	spatial::EmitterId,
+	Duration, PanLaw, Tempo, Value,
 };
 
+use thiserror::Error;
+
 use super::InstanceId;
 
 /// A track index for an instance to play on.
@@ -73,6 +77,211 @@ impl From<Option<f64>> for InstanceLoopStart {
 	}
 }
 
+/// When an instance should start, or when a change to its
+/// playback state should take effect.
+///
+/// The command waits for the delay (if there is one), and
+/// then waits for the metronome to reach the next interval
+/// (if there is one).
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(default)
+)]
+pub struct InstanceTiming {
+	/// How long to wait before the command takes effect.
+	///
+	/// Delays in beats are measured using the tempo of the
+	/// metronome. If there's no metronome, delays in beats
+	/// are skipped.
+	pub delay: Option<Duration>,
+	/// The metronome to use for delays in beats and
+	/// quantization.
+	pub metronome: Option<MetronomeId>,
+	/// The metronome interval (in beats) to wait for
+	/// after the delay.
+	pub interval: Option<f64>,
+}
+
+impl InstanceTiming {
+	/// Creates a new `InstanceTiming` that takes effect immediately.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets how long to wait before the command takes effect.
+	pub fn delay(self, delay: Duration) -> Self {
+		Self {
+			delay: Some(delay),
+			..self
+		}
+	}
+
+	/// Sets the metronome to use for delays in beats.
+	pub fn metronome(self, metronome: impl Into<MetronomeId>) -> Self {
+		Self {
+			metronome: Some(metronome.into()),
+			..self
+		}
+	}
+
+	/// Makes the command wait until the metronome reaches
+	/// the next multiple of `interval` beats.
+	pub fn quantize(self, metronome: impl Into<MetronomeId>, interval: f64) -> Self {
+		Self {
+			metronome: Some(metronome.into()),
+			interval: Some(interval),
+			..self
+		}
+	}
+
+	pub(crate) fn is_immediate(&self) -> bool {
+		self.delay.is_none() && self.interval.is_none()
+	}
+}
+
+/// The maximum number of sends an instance can have.
+pub const MAX_INSTANCE_SENDS: usize = 4;
+
+/// Something that can go wrong when adding a send
+/// to [`InstanceSettings`].
+#[derive(Debug, Error)]
+pub enum InstanceSendError {
+	/// The instance already has [`MAX_INSTANCE_SENDS`] sends.
+	#[error("Instances cannot have more than {} sends", MAX_INSTANCE_SENDS)]
+	TooManySends,
+}
+
+/// A copy of an instance's output that's sent to
+/// another mixer track.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct InstanceSend {
+	/// The track to send the audio to.
+	pub track: TrackIndex,
+	/// The volume of the sent audio.
+	pub volume: Value<f64>,
+	/// Whether to send the audio before the instance's volume
+	/// is applied, so the send level doesn't change when the
+	/// instance's volume does.
+	///
+	/// Pre-volume sends are still affected by fades, and
+	/// they include the output of the instance's effects,
+	/// since those are applied before the instance's volume.
+	pub pre_volume: bool,
+}
+
+impl InstanceSend {
+	/// Creates a new post-volume send to a track.
+	pub fn new(track: impl Into<TrackIndex>, volume: impl Into<Value<f64>>) -> Self {
+		Self {
+			track: track.into(),
+			volume: volume.into(),
+			pre_volume: false,
+		}
+	}
+
+	/// Sends the audio before the instance's volume is applied.
+	pub fn pre_volume(self) -> Self {
+		Self {
+			pre_volume: true,
+			..self
+		}
+	}
+}
+
+/// Settings for playing an instance as a stream of short,
+/// overlapping grains of audio.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(default)
+)]
+pub struct GranularSettings {
+	/// The length of each grain (in seconds).
+	pub grain_size: Value<f64>,
+	/// How many grains start per second.
+	///
+	/// When grains overlap, the output is scaled down by the
+	/// average number of overlapping grains, so changing the
+	/// density or grain size doesn't change the volume much.
+	pub density: Value<f64>,
+	/// The position in the audio grains are read from (in seconds).
+	pub scan_position: Value<f64>,
+	/// The maximum random offset (in seconds) added to the
+	/// position each grain starts at.
+	pub position_jitter: Value<f64>,
+	/// The maximum random offset (in semitones) added to the
+	/// pitch of each grain.
+	pub pitch_jitter: Value<f64>,
+}
+
+impl GranularSettings {
+	/// Creates a new `GranularSettings` with the default settings.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the length of each grain (in seconds).
+	pub fn grain_size<V: Into<Value<f64>>>(self, grain_size: V) -> Self {
+		Self {
+			grain_size: grain_size.into(),
+			..self
+		}
+	}
+
+	/// Sets how many grains start per second.
+	pub fn density<V: Into<Value<f64>>>(self, density: V) -> Self {
+		Self {
+			density: density.into(),
+			..self
+		}
+	}
+
+	/// Sets the position in the audio grains are read from (in seconds).
+	pub fn scan_position<V: Into<Value<f64>>>(self, scan_position: V) -> Self {
+		Self {
+			scan_position: scan_position.into(),
+			..self
+		}
+	}
+
+	/// Sets the maximum random offset (in seconds) added to the
+	/// position each grain starts at.
+	pub fn position_jitter<V: Into<Value<f64>>>(self, position_jitter: V) -> Self {
+		Self {
+			position_jitter: position_jitter.into(),
+			..self
+		}
+	}
+
+	/// Sets the maximum random offset (in semitones) added to the
+	/// pitch of each grain.
+	pub fn pitch_jitter<V: Into<Value<f64>>>(self, pitch_jitter: V) -> Self {
+		Self {
+			pitch_jitter: pitch_jitter.into(),
+			..self
+		}
+	}
+}
+
+impl Default for GranularSettings {
+	fn default() -> Self {
+		Self {
+			grain_size: Value::Fixed(0.1),
+			density: Value::Fixed(20.0),
+			scan_position: Value::Fixed(0.0),
+			position_jitter: Value::Fixed(0.0),
+			pitch_jitter: Value::Fixed(0.0),
+		}
+	}
+}
+
 /// Settings for an instance.
 #[derive(Debug, Copy, Clone)]
 #[cfg_attr(
@@ -89,6 +298,31 @@ pub struct InstanceSettings {
 	pub pitch: Value<f64>,
 	/// The panning of the instance (0 = hard left, 1 = hard right).
 	pub panning: Value<f64>,
+	/// How the volume of each channel changes as the
+	/// instance is panned.
+	pub pan_law: PanLaw,
+	/// Whether to pan the instance by moving both of its
+	/// channels to one side instead of turning one of them down.
+	pub balance: bool,
+	/// The stereo width of the instance (0 = mono,
+	/// 1 = unchanged, above 1 = wider).
+	pub width: Value<f64>,
+	/// How fast the instance plays, as a factor of the original
+	/// speed.
+	///
+	/// Unless time stretching is enabled, this also changes the
+	/// pitch of the instance.
+	pub speed: Value<f64>,
+	/// Whether to change the speed and pitch of the instance
+	/// independently by time stretching the audio.
+	///
+	/// When this is enabled, `speed` only changes how fast the
+	/// instance plays, and `pitch` only changes its pitch.
+	pub time_stretch: bool,
+	/// A metronome whose tempo the instance's speed follows,
+	/// and the tempo (in beats per minute) the audio plays at
+	/// when its speed is 1.
+	pub follow_tempo: Option<(MetronomeId, Tempo)>,
 	/// The position to start playing the instance at (in seconds).
 	pub start_position: f64,
 	/// Whether to play the instance in reverse.
@@ -99,8 +333,41 @@ pub struct InstanceSettings {
 	/// Whether the instance should loop, and if so, the position
 	/// it should jump back to when it reaches the end.
 	pub loop_start: InstanceLoopStart,
+	/// The position (in seconds) the instance should jump back
+	/// from when looping. If `None`, the instance will jump back
+	/// when it reaches the end of the sound or arrangement.
+	pub loop_end: Option<f64>,
+	/// How many times the instance should jump back to the loop
+	/// start point before it continues past the loop end point.
+	/// If `None`, the instance will loop until the loop is released
+	/// or the instance is stopped.
+	pub loop_count: Option<usize>,
+	/// The length of the crossfade (in seconds) between the audio
+	/// before the loop end point and the audio before the loop
+	/// start point, which hides clicks at the loop seam.
+	pub loop_crossfade: Option<f64>,
 	/// Which track to play the instance on.
 	pub track: InstanceTrackIndex,
+	/// How many marker events and instance events can be
+	/// queued at a time.
+	pub event_queue_capacity: usize,
+	/// When the instance should start playing.
+	pub start_timing: InstanceTiming,
+	/// Tracks the instance should send copies of its output to
+	/// in addition to the track it plays on.
+	pub sends: [Option<InstanceSend>; MAX_INSTANCE_SENDS],
+	/// The emitter the instance is attached to, which sets
+	/// the volume and panning of the instance based on where
+	/// the emitter is relative to the listener.
+	///
+	/// The instance's own panning is ignored while the emitter exists.
+	pub emitter: Option<EmitterId>,
+	/// Whether to play the instance as a stream of grains, and
+	/// if so, the settings for the grains.
+	///
+	/// Granular instances play until they're stopped, and they
+	/// ignore the start position, speed, and loop settings.
+	pub granular: Option<GranularSettings>,
 }
 
 impl InstanceSettings {
@@ -141,6 +408,63 @@ impl InstanceSettings {
 		}
 	}
 
+	/// Sets how the volume of each channel changes as the
+	/// instance is panned.
+	pub fn pan_law(self, pan_law: PanLaw) -> Self {
+		Self { pan_law, ..self }
+	}
+
+	/// Pans the instance by moving both of its channels to one
+	/// side, so panning a stereo sound hard left or right
+	/// doesn't drop one of its channels.
+	pub fn balance(self) -> Self {
+		Self {
+			balance: true,
+			..self
+		}
+	}
+
+	/// Sets the stereo width of the instance.
+	pub fn width<W: Into<Value<f64>>>(self, width: W) -> Self {
+		Self {
+			width: width.into(),
+			..self
+		}
+	}
+
+	/// Sets the speed of the instance.
+	pub fn speed<S: Into<Value<f64>>>(self, speed: S) -> Self {
+		Self {
+			speed: speed.into(),
+			..self
+		}
+	}
+
+	/// Enables time stretching, which lets the speed and pitch
+	/// of the instance be changed independently.
+	pub fn time_stretch(self) -> Self {
+		Self {
+			time_stretch: true,
+			..self
+		}
+	}
+
+	/// Makes the speed of the instance follow the tempo of a
+	/// metronome.
+	///
+	/// `tempo` is the tempo the audio was written at. The instance's
+	/// speed is multiplied by the metronome's tempo divided by `tempo`,
+	/// so a loop stays in time with the metronome when its tempo
+	/// changes. This is usually combined with
+	/// [`time_stretch`](InstanceSettings::time_stretch) so the pitch
+	/// of the audio doesn't change with the tempo.
+	pub fn follow_tempo(self, metronome: impl Into<MetronomeId>, tempo: Tempo) -> Self {
+		Self {
+			follow_tempo: Some((metronome.into(), tempo)),
+			..self
+		}
+	}
+
 	/// Sets where in the sound playback will start (in seconds).
 	pub fn start_position(self, start_position: f64) -> Self {
 		Self {
@@ -173,6 +497,34 @@ impl InstanceSettings {
 		}
 	}
 
+	/// Sets the position the instance will jump back from when
+	/// looping (in seconds).
+	pub fn loop_end(self, loop_end: f64) -> Self {
+		Self {
+			loop_end: Some(loop_end),
+			..self
+		}
+	}
+
+	/// Sets how many times the instance will loop before
+	/// continuing past the loop end point.
+	pub fn loop_count(self, loop_count: usize) -> Self {
+		Self {
+			loop_count: Some(loop_count),
+			..self
+		}
+	}
+
+	/// Sets the length of the crossfade between the audio before
+	/// the loop end point and the audio before the loop start point
+	/// (in seconds).
+	pub fn loop_crossfade(self, duration: f64) -> Self {
+		Self {
+			loop_crossfade: Some(duration),
+			..self
+		}
+	}
+
 	/// Sets the track the instance will play on.
 	pub fn track<T: Into<InstanceTrackIndex>>(self, track: T) -> Self {
 		Self {
@@ -181,6 +533,81 @@ impl InstanceSettings {
 		}
 	}
 
+	/// Sets how many marker events and instance events can
+	/// be queued at a time.
+	pub fn event_queue_capacity(self, event_queue_capacity: usize) -> Self {
+		Self {
+			event_queue_capacity,
+			..self
+		}
+	}
+
+	/// Sets when the instance should start playing.
+	///
+	/// If the instance has to wait and
+	/// [`num_instances`](crate::manager::AudioManagerSettings::num_instances)
+	/// commands are already waiting, the instance is never started
+	/// and its state is set to
+	/// [`InstanceState::Stopped`](super::InstanceState::Stopped).
+	pub fn start_timing(self, start_timing: InstanceTiming) -> Self {
+		Self {
+			start_timing,
+			..self
+		}
+	}
+
+	/// Sends a copy of the instance's output to another track.
+	///
+	/// If the instance already has a send to the track, it's
+	/// replaced.
+	///
+	/// **An instance can have at most [`MAX_INSTANCE_SENDS`] sends.**
+	/// Adding a send to another track after that panics in debug
+	/// builds, and the new send is ignored in release builds. Use
+	/// [`try_send`](InstanceSettings::try_send) to handle this case.
+	pub fn send(self, send: InstanceSend) -> Self {
+		self.try_send(send).unwrap_or_else(|error| {
+			if cfg!(debug_assertions) {
+				panic!("{}", error);
+			}
+			self
+		})
+	}
+
+	/// Sends a copy of the instance's output to another track,
+	/// or returns an error if the instance already has
+	/// [`MAX_INSTANCE_SENDS`] sends to other tracks.
+	///
+	/// If the instance already has a send to the track, it's
+	/// replaced.
+	pub fn try_send(mut self, send: InstanceSend) -> Result<Self, InstanceSendError> {
+		let slot = self
+			.sends
+			.iter()
+			.position(|existing| matches!(existing, Some(existing) if existing.track == send.track))
+			.or_else(|| self.sends.iter().position(Option::is_none))
+			.ok_or(InstanceSendError::TooManySends)?;
+		self.sends[slot] = Some(send);
+		Ok(self)
+	}
+
+	/// Attaches the instance to an emitter.
+	pub fn emitter(self, emitter: impl Into<EmitterId>) -> Self {
+		Self {
+			emitter: Some(emitter.into()),
+			..self
+		}
+	}
+
+	/// Plays the instance as a stream of short, overlapping
+	/// grains of audio.
+	pub fn granular(self, settings: GranularSettings) -> Self {
+		Self {
+			granular: Some(settings),
+			..self
+		}
+	}
+
 	pub(crate) fn into_internal(
 		self,
 		duration: f64,
@@ -192,6 +619,12 @@ impl InstanceSettings {
 			volume: self.volume,
 			pitch: self.pitch,
 			panning: self.panning,
+			pan_law: self.pan_law,
+			balance: self.balance,
+			width: self.width,
+			speed: self.speed,
+			time_stretch: self.time_stretch,
+			follow_tempo: self.follow_tempo,
 			start_position: if self.reverse {
 				duration - self.start_position
 			} else {
@@ -204,6 +637,13 @@ impl InstanceSettings {
 				InstanceLoopStart::None => None,
 				InstanceLoopStart::Custom(position) => Some(position),
 			},
+			loop_end: self.loop_end,
+			loop_count: self.loop_count,
+			loop_crossfade: self.loop_crossfade,
+			start_timing: self.start_timing,
+			sends: self.sends,
+			emitter: self.emitter,
+			granular: self.granular,
 			track: match self.track {
 				InstanceTrackIndex::DefaultForSound => default_track,
 				InstanceTrackIndex::Custom(track) => track,
@@ -219,11 +659,25 @@ impl Default for InstanceSettings {
 			volume: Value::Fixed(1.0),
 			pitch: Value::Fixed(1.0),
 			panning: Value::Fixed(0.5),
+			pan_law: PanLaw::default(),
+			balance: false,
+			width: Value::Fixed(1.0),
+			speed: Value::Fixed(1.0),
+			time_stretch: false,
+			follow_tempo: None,
 			start_position: 0.0,
 			reverse: false,
 			fade_in_tween: None,
 			loop_start: InstanceLoopStart::default(),
+			loop_end: None,
+			loop_count: None,
+			loop_crossfade: None,
 			track: InstanceTrackIndex::default(),
+			event_queue_capacity: 10,
+			start_timing: InstanceTiming::default(),
+			sends: [None; MAX_INSTANCE_SENDS],
+			emitter: None,
+			granular: None,
 		}
 	}
 }
@@ -233,13 +687,33 @@ pub(crate) struct InternalInstanceSettings {
 	pub volume: Value<f64>,
 	pub pitch: Value<f64>,
 	pub panning: Value<f64>,
+	pub pan_law: PanLaw,
+	pub balance: bool,
+	pub width: Value<f64>,
+	pub speed: Value<f64>,
+	pub time_stretch: bool,
+	pub follow_tempo: Option<(MetronomeId, Tempo)>,
 	pub start_position: f64,
 	pub reverse: bool,
 	pub fade_in_tween: Option<Tween>,
 	pub loop_start: Option<f64>,
+	pub loop_end: Option<f64>,
+	pub loop_count: Option<usize>,
+	pub loop_crossfade: Option<f64>,
+	pub start_timing: InstanceTiming,
+	pub sends: [Option<InstanceSend>; MAX_INSTANCE_SENDS],
+	pub emitter: Option<EmitterId>,
+	pub granular: Option<GranularSettings>,
 	pub track: TrackIndex,
 }
 
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct InternalCrossfadeSettings {
+	pub fade_out_tween: Option<Tween>,
+	pub match_position: bool,
+	pub timing: InstanceTiming,
+}
+
 /// Settings for pausing an instance.
 #[derive(Debug, Copy, Clone)]
 #[cfg_attr(
@@ -251,6 +725,8 @@ pub struct PauseInstanceSettings {
 	/// Whether to fade the instance to silence, and if so,
 	/// the tween to use.
 	pub fade_tween: Option<Tween>,
+	/// When the instance should pause.
+	pub timing: InstanceTiming,
 }
 
 impl PauseInstanceSettings {
@@ -266,6 +742,11 @@ impl PauseInstanceSettings {
 			..self
 		}
 	}
+
+	/// Sets when the instance should pause.
+	pub fn timing(self, timing: InstanceTiming) -> Self {
+		Self { timing, ..self }
+	}
 }
 
 impl Default for PauseInstanceSettings {
@@ -276,6 +757,7 @@ impl Default for PauseInstanceSettings {
 				easing: Easing::Linear,
 				ease_direction: EaseDirection::In,
 			}),
+			timing: InstanceTiming::default(),
 		}
 	}
 }
@@ -294,6 +776,8 @@ pub struct ResumeInstanceSettings {
 	/// Whether to seek the instance backwards to the playback
 	/// position it was at when it was paused.
 	pub rewind_to_pause_position: bool,
+	/// When the instance should resume.
+	pub timing: InstanceTiming,
 }
 
 impl ResumeInstanceSettings {
@@ -318,6 +802,11 @@ impl ResumeInstanceSettings {
 			..self
 		}
 	}
+
+	/// Sets when the instance should resume.
+	pub fn timing(self, timing: InstanceTiming) -> Self {
+		Self { timing, ..self }
+	}
 }
 
 impl Default for ResumeInstanceSettings {
@@ -329,6 +818,7 @@ impl Default for ResumeInstanceSettings {
 				ease_direction: EaseDirection::In,
 			}),
 			rewind_to_pause_position: false,
+			timing: InstanceTiming::default(),
 		}
 	}
 }
@@ -344,6 +834,8 @@ pub struct StopInstanceSettings {
 	/// Whether to fade the instance to silence, and if so,
 	/// the tween to use.
 	pub fade_tween: Option<Tween>,
+	/// When the instance should stop.
+	pub timing: InstanceTiming,
 }
 
 impl StopInstanceSettings {
@@ -359,6 +851,11 @@ impl StopInstanceSettings {
 			..self
 		}
 	}
+
+	/// Sets when the instance should stop.
+	pub fn timing(self, timing: InstanceTiming) -> Self {
+		Self { timing, ..self }
+	}
 }
 
 impl Default for StopInstanceSettings {
@@ -369,6 +866,135 @@ impl Default for StopInstanceSettings {
 				easing: Easing::Linear,
 				ease_direction: EaseDirection::In,
 			}),
+			timing: InstanceTiming::default(),
+		}
+	}
+}
+
+/// Settings for crossfading from one instance to a new
+/// instance of a sound or arrangement.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(default)
+)]
+pub struct CrossfadeSettings {
+	/// How long the crossfade lasts (in seconds).
+	pub duration: f64,
+	/// Whether to start the new instance at the playback
+	/// position of the old instance.
+	///
+	/// If the old instance's position is past the end of the new
+	/// sound or arrangement, it wraps around to the beginning.
+	pub match_position: bool,
+	/// When the crossfade should start.
+	pub timing: InstanceTiming,
+	/// The settings for the new instance.
+	///
+	/// The fade-in tween and start timing are ignored, since the
+	/// new instance starts with the crossfade.
+	pub instance_settings: InstanceSettings,
+}
+
+impl CrossfadeSettings {
+	/// Creates a new `CrossfadeSettings` with the default settings.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets how long the crossfade lasts (in seconds).
+	pub fn duration(self, duration: f64) -> Self {
+		Self { duration, ..self }
+	}
+
+	/// Starts the new instance at the playback position
+	/// of the old instance.
+	pub fn match_position(self) -> Self {
+		Self {
+			match_position: true,
+			..self
+		}
+	}
+
+	/// Sets when the crossfade should start.
+	pub fn timing(self, timing: InstanceTiming) -> Self {
+		Self { timing, ..self }
+	}
+
+	/// Sets the settings for the new instance.
+	pub fn instance_settings(self, instance_settings: InstanceSettings) -> Self {
+		Self {
+			instance_settings,
+			..self
+		}
+	}
+
+	/// Returns the tween used to fade in the new instance.
+	pub(crate) fn fade_in_tween(&self) -> Option<Tween> {
+		if self.duration <= 0.0 {
+			return None;
+		}
+		Some(Tween {
+			duration: self.duration,
+			easing: Easing::Sine,
+			ease_direction: EaseDirection::Out,
+		})
+	}
+
+	/// Returns the settings the audio thread needs to
+	/// crossfade from the old instance to the new one.
+	pub(crate) fn into_internal(self) -> InternalCrossfadeSettings {
+		InternalCrossfadeSettings {
+			fade_out_tween: self.fade_out_tween(),
+			match_position: self.match_position,
+			timing: self.timing,
+		}
+	}
+
+	/// Returns the tween used to fade out the old instance.
+	fn fade_out_tween(&self) -> Option<Tween> {
+		if self.duration <= 0.0 {
+			return None;
+		}
+		Some(Tween {
+			duration: self.duration,
+			easing: Easing::Sine,
+			ease_direction: EaseDirection::In,
+		})
+	}
+}
+
+impl Default for CrossfadeSettings {
+	fn default() -> Self {
+		Self {
+			duration: 1.0,
+			match_position: false,
+			timing: InstanceTiming::default(),
+			instance_settings: InstanceSettings::default(),
 		}
 	}
 }
+
+/// What should happen when a sound or arrangement is played
+/// while it already has the maximum number of instances playing.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum InstanceLimitBehavior {
+	/// The new instance will not be played.
+	IgnoreNew,
+	/// The instance that was started the longest time
+	/// ago will be stopped.
+	StopOldest,
+	/// The instance with the lowest volume will be stopped.
+	StopQuietest,
+}
+
+impl Default for InstanceLimitBehavior {
+	fn default() -> Self {
+		Self::StopOldest
+	}
+}
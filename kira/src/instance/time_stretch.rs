@@ -0,0 +1,184 @@
+use std::f64::consts::PI;
+
+use crate::Frame;
+
+/// The length of each grain of audio (in seconds).
+const GRAIN_DURATION: f64 = 0.06;
+/// The number of points compared when looking for the
+/// best place to start a grain.
+const WINDOW_POINTS: usize = 128;
+/// The number of possible grain start points considered.
+const SEARCH_POINTS: usize = 128;
+/// The spacing between the grain start points considered
+/// in the first, coarse pass of the search.
+const COARSE_SEARCH_STEP: usize = 4;
+/// The amount of time (in seconds) between the points
+/// compared when looking for the best place to start a grain.
+const POINT_SPACING: f64 = 0.0000625;
+
+#[derive(Debug, Copy, Clone)]
+struct Grain {
+	/// The position in the audio the grain started at (in seconds).
+	start: f64,
+	/// How fast the grain moves through the audio.
+	rate: f64,
+	/// How long the grain has been playing (in seconds).
+	age: f64,
+}
+
+impl Grain {
+	fn finished(&self) -> bool {
+		self.age >= GRAIN_DURATION
+	}
+
+	fn read_position(&self) -> f64 {
+		self.start + self.age * self.rate
+	}
+
+	/// The Hann window used to fade the grain in and out.
+	/// Two grains offset by half of the grain duration
+	/// always add up to a volume of 1.
+	fn window(&self) -> f32 {
+		(0.5 - 0.5 * (2.0 * PI * self.age / GRAIN_DURATION).cos()) as f32
+	}
+}
+
+/// Changes the speed of audio without changing its pitch (and
+/// vice versa) using waveform similarity overlap-add (WSOLA).
+///
+/// Overlapping grains of audio are read from around the current
+/// playback position at the requested pitch. Each new grain starts
+/// at the point near the playback position whose waveform best
+/// matches the audio the previous grain would have played next,
+/// which avoids the phase cancellation simple overlap-add causes.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct TimeStretcher {
+	grains: [Option<Grain>; 2],
+	next_grain_index: usize,
+	time_until_next_grain: f64,
+}
+
+impl TimeStretcher {
+	pub fn new() -> Self {
+		Self {
+			grains: [None; 2],
+			next_grain_index: 0,
+			time_until_next_grain: 0.0,
+		}
+	}
+
+	/// Finds the best position near `position` to start a grain that
+	/// overlaps with `previous_grain`.
+	fn find_grain_start(
+		previous_grain: &Grain,
+		position: f64,
+		rate: f64,
+		read: &impl Fn(f64) -> Frame,
+	) -> f64 {
+		let spacing = POINT_SPACING * rate;
+		let read_mono = |position: f64| {
+			let frame = read(position);
+			frame.left + frame.right
+		};
+		let mut reference = [0.0; WINDOW_POINTS];
+		let natural_position = previous_grain.read_position();
+		for (i, point) in reference.iter_mut().enumerate() {
+			*point = read_mono(natural_position + i as f64 * spacing);
+		}
+		let search_start = position - (SEARCH_POINTS / 2) as f64 * spacing;
+		let mut candidates = [0.0; SEARCH_POINTS + WINDOW_POINTS];
+		for (i, point) in candidates.iter_mut().enumerate() {
+			*point = read_mono(search_start + i as f64 * spacing);
+		}
+		// running sums of the candidates' energy, used to normalize
+		// the correlations so louder sections aren't favored
+		let mut energy_sums = [0.0; SEARCH_POINTS + WINDOW_POINTS + 1];
+		for (i, point) in candidates.iter().enumerate() {
+			energy_sums[i + 1] = energy_sums[i] + point * point;
+		}
+		let score = |offset: usize, point_step: usize| {
+			let correlation: f32 = reference
+				.iter()
+				.zip(&candidates[offset..])
+				.step_by(point_step)
+				.map(|(a, b)| a * b)
+				.sum();
+			let energy =
+				(energy_sums[offset + WINDOW_POINTS] - energy_sums[offset]) / point_step as f32;
+			correlation / (energy.max(0.0) + f32::EPSILON).sqrt()
+		};
+		// search every few candidates using every other point,
+		// then check the candidates around the best match using
+		// all of the points. this takes about a fifth of the work
+		// of comparing every candidate in full.
+		let mut best_index = SEARCH_POINTS / 2;
+		let mut best_score = f32::NEG_INFINITY;
+		for offset in (0..SEARCH_POINTS).step_by(COARSE_SEARCH_STEP) {
+			let score = score(offset, 2);
+			if score > best_score {
+				best_score = score;
+				best_index = offset;
+			}
+		}
+		let coarse_index = best_index;
+		best_score = f32::NEG_INFINITY;
+		let fine_start = coarse_index.saturating_sub(COARSE_SEARCH_STEP - 1);
+		let fine_end = (coarse_index + COARSE_SEARCH_STEP).min(SEARCH_POINTS);
+		for offset in fine_start..fine_end {
+			let score = score(offset, 1);
+			if score > best_score {
+				best_score = score;
+				best_index = offset;
+			}
+		}
+		search_start + best_index as f64 * spacing
+	}
+
+	/// Produces the next frame of time-stretched audio.
+	///
+	/// - `position` is the playback position (in seconds)
+	/// - `rate` is how fast grains should move through the audio,
+	///   which is negative for reverse playback
+	/// - `read` gets the frame of audio at a position
+	pub fn process(
+		&mut self,
+		dt: f64,
+		position: f64,
+		rate: f64,
+		read: impl Fn(f64) -> Frame,
+	) -> Frame {
+		if self.time_until_next_grain <= 0.0 {
+			let previous_grain =
+				self.grains[(self.next_grain_index + 1) % 2].filter(|grain| !grain.finished());
+			self.grains[self.next_grain_index] = Some(match previous_grain {
+				Some(previous_grain) => Grain {
+					start: Self::find_grain_start(&previous_grain, position, rate, &read),
+					rate,
+					age: 0.0,
+				},
+				// start the first grain halfway through so the audio
+				// doesn't fade in
+				None => Grain {
+					start: position - GRAIN_DURATION / 2.0 * rate,
+					rate,
+					age: GRAIN_DURATION / 2.0,
+				},
+			});
+			self.next_grain_index = (self.next_grain_index + 1) % 2;
+			// the first grain is already halfway through, so the
+			// grain that overlaps with it should start right away
+			if previous_grain.is_some() {
+				self.time_until_next_grain += GRAIN_DURATION / 2.0;
+			}
+		}
+		let mut out = Frame::from_mono(0.0);
+		for grain in self.grains.iter_mut().flatten() {
+			if !grain.finished() {
+				out += read(grain.read_position()) * grain.window();
+				grain.age += dt;
+			}
+		}
+		self.time_until_next_grain -= dt;
+		out
+	}
+}
@@ -0,0 +1,48 @@
+use crate::{
+	parameter::{tween::Tween, Parameter, Parameters},
+	util::lerp,
+	value::CachedValue,
+	Value,
+};
+
+/// A [`CachedValue`] that can move smoothly from its
+/// current value to a new value setting.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct TweenedValue {
+	value: CachedValue<f64>,
+	transition_start: f64,
+	/// How far the value has moved from `transition_start`
+	/// to the new value setting, from 0 to 1.
+	transition: Parameter,
+}
+
+impl TweenedValue {
+	pub fn new(value: Value<f64>, default_value: f64) -> Self {
+		let value = CachedValue::new(value, default_value);
+		Self {
+			value,
+			transition_start: value.value(),
+			transition: Parameter::new(1.0),
+		}
+	}
+
+	pub fn set(&mut self, value: Value<f64>, tween: Option<Tween>) {
+		self.transition_start = self.value();
+		self.value.set(value);
+		self.transition = Parameter::new(if tween.is_some() { 0.0 } else { 1.0 });
+		self.transition.set(1.0, tween);
+	}
+
+	pub fn update(&mut self, dt: f64, parameters: &Parameters) {
+		self.value.update(parameters);
+		self.transition.update(dt);
+	}
+
+	pub fn value(&self) -> f64 {
+		lerp(
+			self.transition_start,
+			self.value.value(),
+			self.transition.value(),
+		)
+	}
+}
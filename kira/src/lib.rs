@@ -114,19 +114,24 @@ mod frame;
 pub mod group;
 pub mod instance;
 pub mod manager;
+mod marker;
 pub mod metronome;
 pub mod mixer;
+mod pan_law;
 pub mod parameter;
 mod playable;
 mod resource;
 pub mod sequence;
 pub mod sound;
+pub mod spatial;
 mod tempo;
 mod util;
 mod value;
 
 pub use duration::Duration;
 pub use frame::Frame;
+pub use marker::Marker;
+pub use pan_law::PanLaw;
 pub use playable::PlayableId;
 pub use tempo::Tempo;
 pub use value::{CachedValue, Value};
@@ -1,16 +1,17 @@
 use crate::{
 	arrangement::ArrangementId, audio_stream::AudioStreamId, group::GroupId,
 	metronome::MetronomeId, mixer::SubTrackId, parameter::ParameterId, sound::SoundId,
+	spatial::EmitterId,
 };
 
 use indexmap::IndexSet;
 
 use super::{
 	error::{
-		AddArrangementError, AddGroupError, AddMetronomeError, AddParameterError, AddSoundError,
-		AddStreamError, AddTrackError, RemoveArrangementError, RemoveGroupError,
-		RemoveMetronomeError, RemoveParameterError, RemoveSoundError, RemoveStreamError,
-		RemoveTrackError,
+		AddArrangementError, AddEmitterError, AddGroupError, AddMetronomeError, AddParameterError,
+		AddSoundError, AddStreamError, AddTrackError, RemoveArrangementError, RemoveEmitterError,
+		RemoveGroupError, RemoveMetronomeError, RemoveParameterError, RemoveSoundError,
+		RemoveStreamError, RemoveTrackError,
 	},
 	AudioManagerSettings,
 };
@@ -23,6 +24,7 @@ pub struct ActiveIds {
 	pub active_group_ids: IndexSet<GroupId>,
 	pub active_metronome_ids: IndexSet<MetronomeId>,
 	pub active_stream_ids: IndexSet<AudioStreamId>,
+	pub active_emitter_ids: IndexSet<EmitterId>,
 }
 
 impl ActiveIds {
@@ -35,6 +37,7 @@ impl ActiveIds {
 			active_group_ids: IndexSet::with_capacity(settings.num_groups),
 			active_metronome_ids: IndexSet::with_capacity(settings.num_metronomes),
 			active_stream_ids: IndexSet::with_capacity(settings.num_streams),
+			active_emitter_ids: IndexSet::with_capacity(settings.num_emitters),
 		}
 	}
 
@@ -145,4 +148,19 @@ impl ActiveIds {
 		}
 		Ok(())
 	}
+
+	pub fn add_emitter_id(&mut self, id: EmitterId) -> Result<(), AddEmitterError> {
+		if self.active_emitter_ids.len() >= self.active_emitter_ids.capacity() {
+			return Err(AddEmitterError::EmitterLimitReached);
+		}
+		self.active_emitter_ids.insert(id);
+		Ok(())
+	}
+
+	pub fn remove_emitter_id(&mut self, id: EmitterId) -> Result<(), RemoveEmitterError> {
+		if !self.active_emitter_ids.remove(&id) {
+			return Err(RemoveEmitterError::NoEmitterWithId(id));
+		}
+		Ok(())
+	}
 }
@@ -1,25 +1,113 @@
 use crate::{
 	command::InstanceCommand,
 	group::groups::Groups,
-	instance::{Instance, InstanceId, StopInstanceSettings},
+	instance::{
+		Instance, InstanceEvent, InstanceId, InstanceLimitBehavior, InstanceTiming,
+		StopInstanceSettings,
+	},
+	metronome::Metronomes,
+	mixer::effect_slot::EffectSlot,
 	parameter::Parameters,
 	playable::{PlayableId, Playables},
+	resource::Resource,
+	spatial::{BinauralRenderer, SpatialScene},
+	Duration, Tempo,
 };
+use flume::Sender;
 use indexmap::IndexMap;
 
 use super::mixer::Mixer;
 
+/// A command that's waiting for its delay or
+/// metronome interval before taking effect.
+struct PendingCommand {
+	command: InstanceCommand,
+	/// The order the command was received in, used to run
+	/// commands that are ready on the same frame in order.
+	index: u64,
+	timing: InstanceTiming,
+	/// How much of the delay is left, from 1 to 0.
+	delay_remaining: f64,
+}
+
+impl PendingCommand {
+	fn new(command: InstanceCommand, index: u64) -> Self {
+		let timing = command.timing();
+		Self {
+			command,
+			index,
+			timing,
+			delay_remaining: if timing.delay.is_some() { 1.0 } else { 0.0 },
+		}
+	}
+
+	/// Updates the delay timer and returns `true` if the
+	/// command should take effect on this frame.
+	fn update(&mut self, dt: f64, metronomes: &Metronomes) -> bool {
+		let metronome = self
+			.timing
+			.metronome
+			.and_then(|metronome| metronomes.get(metronome));
+		if self.delay_remaining > 0.0 {
+			let delay = match (self.timing.delay, metronome) {
+				(Some(Duration::Beats(_)), None) | (None, _) => 0.0,
+				(Some(delay), Some(metronome)) => delay.in_seconds(metronome.effective_tempo()),
+				(Some(delay), None) => delay.in_seconds(Tempo(0.0)),
+			};
+			if delay > 0.0 {
+				self.delay_remaining -= dt / delay;
+			} else {
+				self.delay_remaining = 0.0;
+			}
+			if self.delay_remaining > 0.0 {
+				return false;
+			}
+		}
+		match (self.timing.interval, metronome) {
+			(Some(interval), Some(metronome)) => metronome.interval_passed(interval),
+			_ => true,
+		}
+	}
+}
+
 pub(crate) struct Instances {
-	instances: IndexMap<InstanceId, Instance>,
+	instances: IndexMap<InstanceId, Box<Instance>>,
 	instances_to_remove: Vec<InstanceId>,
+	pending_commands: Vec<PendingCommand>,
+	ready_commands: Vec<PendingCommand>,
+	next_command_index: u64,
+	/// Renderers that aren't being used by any instance. They're
+	/// allocated up front and lent to instances that are rendered
+	/// binaurally, since most instances don't need one.
+	binaural_renderers: Vec<BinauralRenderer>,
 }
 
 impl Instances {
-	pub fn new(capacity: usize) -> Self {
+	pub fn new(capacity: usize, num_binaural_renderers: usize) -> Self {
 		Self {
 			instances: IndexMap::with_capacity(capacity),
 			instances_to_remove: Vec::with_capacity(capacity),
+			pending_commands: Vec::with_capacity(capacity),
+			ready_commands: Vec::with_capacity(capacity),
+			next_command_index: 0,
+			binaural_renderers: (0..num_binaural_renderers)
+				.map(|_| BinauralRenderer::new())
+				.collect(),
+		}
+	}
+
+	/// Takes back an instance's binaural renderer
+	/// and sends the instance to be unloaded.
+	fn unload_instance(
+		binaural_renderers: &mut Vec<BinauralRenderer>,
+		mut instance: Box<Instance>,
+		unloader: &mut Sender<Resource>,
+	) {
+		if let Some(mut renderer) = instance.take_binaural_renderer() {
+			renderer.reset();
+			binaural_renderers.push(renderer);
 		}
+		unloader.try_send(Resource::Instance(instance)).ok();
 	}
 
 	pub fn stop_instances_of(&mut self, playable: PlayableId, settings: StopInstanceSettings) {
@@ -30,58 +118,270 @@ impl Instances {
 		}
 	}
 
+	/// Stops instances of a playable until there's room for
+	/// a new instance under the playable's instance limit.
+	/// The instance being replaced by a crossfade, if any,
+	/// doesn't count toward the limit.
+	///
+	/// Returns `false` if the new instance should not be played.
+	fn enforce_instance_limit(
+		&mut self,
+		playable: PlayableId,
+		max_instances: Option<usize>,
+		behavior: InstanceLimitBehavior,
+		replacing: Option<InstanceId>,
+	) -> bool {
+		let max_instances = match max_instances {
+			Some(max_instances) => max_instances,
+			None => return true,
+		};
+		if max_instances == 0 {
+			return false;
+		}
+		let is_active = |instance: &Instance| {
+			instance.playable_id() == playable
+				&& !instance.stopping()
+				&& Some(instance.id()) != replacing
+		};
+		loop {
+			let num_active_instances = self
+				.instances
+				.values()
+				.filter(|instance| is_active(instance))
+				.count();
+			if num_active_instances < max_instances {
+				return true;
+			}
+			let mut active_instances = self
+				.instances
+				.values_mut()
+				.filter(|instance| is_active(instance));
+			let instance_to_stop = match behavior {
+				InstanceLimitBehavior::IgnoreNew => return false,
+				InstanceLimitBehavior::StopOldest => active_instances.next(),
+				InstanceLimitBehavior::StopQuietest => active_instances
+					.min_by(|a, b| a.effective_volume().total_cmp(&b.effective_volume())),
+			};
+			match instance_to_stop {
+				Some(instance) => {
+					instance.emit_event(InstanceEvent::Stolen);
+					instance.stop(StopInstanceSettings::default());
+				}
+				None => return true,
+			}
+		}
+	}
+
+	/// Stops instances that are waiting to start, removing
+	/// their play commands.
+	fn cancel_pending_plays(
+		&mut self,
+		unloader: &mut Sender<Resource>,
+		mut should_cancel: impl FnMut(&Instance) -> bool,
+	) {
+		let mut i = 0;
+		while i < self.pending_commands.len() {
+			match &self.pending_commands[i].command {
+				InstanceCommand::Play(_, instance)
+				| InstanceCommand::Crossfade(_, _, instance, _)
+					if should_cancel(instance) =>
+				{
+					// pending commands are run in the order they were
+					// received regardless of their place in the list
+					let pending_command = self.pending_commands.swap_remove(i);
+					Self::discard_command(pending_command.command, unloader);
+				}
+				_ => i += 1,
+			}
+		}
+	}
+
+	/// Disposes of a command that won't be run. Instances
+	/// that haven't started are marked as stopped, and
+	/// anything that owns heap memory is sent to the
+	/// unloader.
+	fn discard_command(command: InstanceCommand, unloader: &mut Sender<Resource>) {
+		match command {
+			InstanceCommand::Play(_, mut instance)
+			| InstanceCommand::Crossfade(_, _, mut instance, _) => {
+				instance.reject();
+				unloader.try_send(Resource::Instance(instance)).ok();
+			}
+			InstanceCommand::AddInstanceEffect(_, effect, settings) => {
+				unloader
+					.try_send(Resource::EffectSlot(EffectSlot::new(effect, settings)))
+					.ok();
+			}
+			_ => {}
+		}
+	}
+
+	/// Gets an instance that's playing or waiting to start.
+	fn instance_mut(&mut self, id: InstanceId) -> Option<&mut Instance> {
+		if let Some(instance) = self.instances.get_mut(&id) {
+			return Some(instance);
+		}
+		self.pending_commands
+			.iter_mut()
+			.find_map(|pending_command| match &mut pending_command.command {
+				InstanceCommand::Play(instance_id, instance)
+				| InstanceCommand::Crossfade(_, instance_id, instance, _)
+					if *instance_id == id =>
+				{
+					Some(instance.as_mut())
+				}
+				_ => None,
+			})
+	}
+
+	/// Starts an instance if its playable isn't cooling down
+	/// and there's room for it under the instance limit.
+	///
+	/// Returns `false` if the instance was rejected.
+	fn play(
+		&mut self,
+		instance_id: InstanceId,
+		mut instance: Box<Instance>,
+		replacing: Option<InstanceId>,
+		playables: &mut Playables,
+		unloader: &mut Sender<Resource>,
+	) -> bool {
+		if let Some(mut playable) = playables.playable_mut(instance.playable_id()) {
+			if !playable.cooling_down()
+				&& self.enforce_instance_limit(
+					instance.playable_id(),
+					playable.max_instances(),
+					playable.instance_limit_behavior(),
+					replacing,
+				) {
+				// if we're at the instance limit, remove the instance that was
+				// started the longest time ago.
+				if self.instances.len() >= self.instances.capacity() {
+					if let Some((_, instance)) = self.instances.shift_remove_index(0) {
+						instance.emit_event(InstanceEvent::Stolen);
+						instance.emit_event(InstanceEvent::Stopped);
+						Self::unload_instance(&mut self.binaural_renderers, instance, unloader);
+					}
+				}
+				instance.start();
+				instance.emit_event(InstanceEvent::Started);
+				self.instances.insert(instance_id, instance);
+				playable.start_cooldown();
+				return true;
+			}
+		}
+		Self::discard_command(InstanceCommand::Play(instance_id, instance), unloader);
+		false
+	}
+
 	pub fn run_command(
 		&mut self,
 		command: InstanceCommand,
 		playables: &mut Playables,
 		all_groups: &Groups,
+		unloader: &mut Sender<Resource>,
+	) {
+		if command.timing().is_immediate() {
+			self.execute_command(command, playables, all_groups, unloader);
+		} else if self.pending_commands.len() >= self.pending_commands.capacity() {
+			// don't allocate on the audio thread
+			Self::discard_command(command, unloader);
+		} else {
+			self.pending_commands
+				.push(PendingCommand::new(command, self.next_command_index));
+			self.next_command_index += 1;
+		}
+	}
+
+	/// Runs the commands whose delays and metronome
+	/// intervals have passed.
+	pub fn update_pending_commands(
+		&mut self,
+		dt: f64,
+		playables: &mut Playables,
+		all_groups: &Groups,
+		metronomes: &Metronomes,
+		unloader: &mut Sender<Resource>,
+	) {
+		let mut i = 0;
+		while i < self.pending_commands.len() {
+			if self.pending_commands[i].update(dt, metronomes) {
+				self.ready_commands
+					.push(self.pending_commands.swap_remove(i));
+			} else {
+				i += 1;
+			}
+		}
+		// swap_remove changes the order of the pending commands,
+		// so restore the order they were received in
+		self.ready_commands
+			.sort_unstable_by_key(|pending_command| pending_command.index);
+		let mut ready_commands = std::mem::take(&mut self.ready_commands);
+		for pending_command in ready_commands.drain(..) {
+			self.execute_command(pending_command.command, playables, all_groups, unloader);
+		}
+		self.ready_commands = ready_commands;
+	}
+
+	fn execute_command(
+		&mut self,
+		command: InstanceCommand,
+		playables: &mut Playables,
+		all_groups: &Groups,
+		unloader: &mut Sender<Resource>,
 	) {
 		match command {
 			InstanceCommand::Play(instance_id, instance) => {
-				if let Some(mut playable) = playables.playable_mut(instance.playable_id()) {
-					if !playable.cooling_down() {
-						// if we're at the instance limit, remove the instance that was
-						// started the longest time ago.
-						if self.instances.len() >= self.instances.capacity() {
-							self.instances.shift_remove_index(0);
-						}
-						self.instances.insert(instance_id, instance);
-						playable.start_cooldown();
+				self.play(instance_id, instance, None, playables, unloader);
+			}
+			InstanceCommand::Crossfade(from, to, mut instance, settings) => {
+				if settings.match_position {
+					if let Some(old_instance) = self.instance_mut(from) {
+						instance.seek_to_wrapped(old_instance.position());
 					}
 				}
+				// the old instance keeps playing if the new one
+				// is held back by a cooldown or instance limit
+				if self.play(to, instance, Some(from), playables, unloader) {
+					if let Some(old_instance) = self.instances.get_mut(&from) {
+						old_instance
+							.stop(StopInstanceSettings::new().fade_tween(settings.fade_out_tween));
+					}
+					self.cancel_pending_plays(unloader, |instance| instance.id() == from);
+				}
 			}
-			InstanceCommand::SetInstanceVolume(id, value) => {
-				if let Some(instance) = self.instances.get_mut(&id) {
-					instance.set_volume(value);
+			InstanceCommand::SetInstanceVolume(id, value, tween) => {
+				if let Some(instance) = self.instance_mut(id) {
+					instance.set_volume(value, tween);
 				}
 			}
-			InstanceCommand::SetInstancePitch(id, value) => {
-				if let Some(instance) = self.instances.get_mut(&id) {
-					instance.set_pitch(value);
+			InstanceCommand::SetInstancePitch(id, value, tween) => {
+				if let Some(instance) = self.instance_mut(id) {
+					instance.set_pitch(value, tween);
 				}
 			}
-			InstanceCommand::SetInstancePanning(id, value) => {
-				if let Some(instance) = self.instances.get_mut(&id) {
-					instance.set_panning(value);
+			InstanceCommand::SetInstancePanning(id, value, tween) => {
+				if let Some(instance) = self.instance_mut(id) {
+					instance.set_panning(value, tween);
 				}
 			}
 			InstanceCommand::SeekInstance(id, offset) => {
-				if let Some(instance) = self.instances.get_mut(&id) {
+				if let Some(instance) = self.instance_mut(id) {
 					instance.seek(offset);
 				}
 			}
 			InstanceCommand::SeekInstanceTo(id, position) => {
-				if let Some(instance) = self.instances.get_mut(&id) {
+				if let Some(instance) = self.instance_mut(id) {
 					instance.seek_to(position);
 				}
 			}
 			InstanceCommand::PauseInstance(id, settings) => {
-				if let Some(instance) = self.instances.get_mut(&id) {
+				if let Some(instance) = self.instance_mut(id) {
 					instance.pause(settings);
 				}
 			}
 			InstanceCommand::ResumeInstance(id, settings) => {
-				if let Some(instance) = self.instances.get_mut(&id) {
+				if let Some(instance) = self.instance_mut(id) {
 					instance.resume(settings);
 				}
 			}
@@ -89,6 +389,52 @@ impl Instances {
 				if let Some(instance) = self.instances.get_mut(&id) {
 					instance.stop(settings);
 				}
+				// instances that are waiting to start are cancelled
+				// instead, since they never started playing
+				self.cancel_pending_plays(unloader, |instance| instance.id() == id);
+			}
+			InstanceCommand::ReleaseInstanceLoop(id) => {
+				if let Some(instance) = self.instance_mut(id) {
+					instance.release_loop();
+				}
+			}
+			InstanceCommand::SetInstanceSpeed(id, value, tween) => {
+				if let Some(instance) = self.instance_mut(id) {
+					instance.set_speed(value, tween);
+				}
+			}
+			InstanceCommand::SetInstanceWidth(id, value, tween) => {
+				if let Some(instance) = self.instance_mut(id) {
+					instance.set_width(value, tween);
+				}
+			}
+			InstanceCommand::SetInstanceSendVolume(id, track, volume, tween) => {
+				if let Some(instance) = self.instance_mut(id) {
+					instance.set_send_volume(track, volume, tween);
+				}
+			}
+			InstanceCommand::AddInstanceEffect(id, effect, settings) => {
+				match self.instance_mut(id) {
+					Some(instance) => instance.add_effect(effect, settings),
+					None => Self::discard_command(
+						InstanceCommand::AddInstanceEffect(id, effect, settings),
+						unloader,
+					),
+				}
+			}
+			InstanceCommand::SetInstanceEffectEnabled(id, effect_id, enabled) => {
+				if let Some(instance) = self.instance_mut(id) {
+					if let Some(effect_slot) = instance.effect_mut(effect_id) {
+						effect_slot.enabled = enabled;
+					}
+				}
+			}
+			InstanceCommand::RemoveInstanceEffect(id, effect_id) => {
+				if let Some(instance) = self.instance_mut(id) {
+					if let Some(effect_slot) = instance.remove_effect(effect_id) {
+						unloader.try_send(Resource::EffectSlot(effect_slot)).ok();
+					}
+				}
 			}
 			InstanceCommand::PauseInstancesOf(playable, settings) => {
 				for (_, instance) in &mut self.instances {
@@ -106,6 +452,7 @@ impl Instances {
 			}
 			InstanceCommand::StopInstancesOf(playable, settings) => {
 				self.stop_instances_of(playable, settings);
+				self.cancel_pending_plays(unloader, |instance| instance.playable_id() == playable);
 			}
 			InstanceCommand::PauseGroup(id, settings) => {
 				for (_, instance) in &mut self.instances {
@@ -133,6 +480,11 @@ impl Instances {
 						}
 					}
 				}
+				self.cancel_pending_plays(unloader, |instance| {
+					playables
+						.playable(instance.playable_id())
+						.map_or(false, |playable| playable.is_in_group(id, all_groups))
+				});
 			}
 			InstanceCommand::PauseInstancesOfSequence(id, settings) => {
 				for (_, instance) in &mut self.instances {
@@ -154,30 +506,534 @@ impl Instances {
 						instance.stop(settings);
 					}
 				}
+				self.cancel_pending_plays(unloader, |instance| instance.sequence_id() == Some(id));
 			}
 		}
 	}
 
+	/// Updates the speeds of instances that follow a
+	/// metronome's tempo.
+	pub fn update_tempo_ratios(&mut self, metronomes: &Metronomes) {
+		for (_, instance) in &mut self.instances {
+			instance.update_tempo_ratio(metronomes);
+		}
+	}
+
 	pub fn process(
 		&mut self,
 		dt: f64,
 		playables: &Playables,
 		mixer: &mut Mixer,
 		parameters: &Parameters,
+		spatial_scene: &SpatialScene,
+		unloader: &mut Sender<Resource>,
 	) {
 		// TODO: simplify this code (preferably by removing self.instances_to_remove)
 		// while making sure every sample of the sound gets played before the instance is removed
 		for (instance_id, instance) in &mut self.instances {
+			if instance.renders_binaurally(spatial_scene) {
+				if !instance.has_binaural_renderer() {
+					// if every renderer is in use, the instance
+					// is panned instead
+					if let Some(renderer) = self.binaural_renderers.pop() {
+						instance.set_binaural_renderer(renderer);
+					}
+				}
+			} else if let Some(mut renderer) = instance.take_binaural_renderer() {
+				renderer.reset();
+				self.binaural_renderers.push(renderer);
+			}
 			if instance.playing() {
-				mixer.add_input(instance.track_index(), instance.get_sample(playables));
+				let output = instance.get_sample(dt, playables, parameters, spatial_scene);
+				mixer.add_input(instance.track_index(), output.post_volume);
+				instance.for_each_send(|track, volume, pre_volume| {
+					let frame = if pre_volume {
+						output.pre_volume
+					} else {
+						output.post_volume
+					};
+					mixer.add_input(track, frame * volume);
+				});
 			}
 			if instance.finished() {
 				self.instances_to_remove.push(*instance_id);
 			}
-			instance.update(dt, parameters);
+			instance.update(dt, playables, parameters, spatial_scene);
 		}
 		for instance_id in self.instances_to_remove.drain(..) {
-			self.instances.shift_remove(&instance_id);
+			if let Some(instance) = self.instances.shift_remove(&instance_id) {
+				Self::unload_instance(&mut self.binaural_renderers, instance, unloader);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use atomic::{Atomic, Ordering};
+	use flume::{Receiver, Sender};
+
+	use crate::{
+		command::{InstanceCommand, MetronomeCommand, ResourceCommand},
+		group::groups::Groups,
+		instance::{
+			CrossfadeSettings, Instance, InstanceEvent, InstanceId, InstanceLimitBehavior,
+			InstanceSettings, InstanceState, InstanceTiming, StopInstanceSettings,
+		},
+		metronome::{Metronome, MetronomeId, MetronomeSettings, Metronomes},
+		mixer::TrackIndex,
+		parameter::Parameters,
+		playable::{PlayableId, Playables},
+		resource::Resource,
+		sound::{Sound, SoundSettings},
+		spatial::SpatialScene,
+		Duration, Frame,
+	};
+
+	use super::Instances;
+
+	const SAMPLE_RATE: u32 = 100;
+
+	/// An instance created for a test, along with the
+	/// means to observe it after it's sent to [`Instances`].
+	struct TestInstance {
+		id: InstanceId,
+		instance: Option<Box<Instance>>,
+		state: Arc<Atomic<InstanceState>>,
+		events: Receiver<InstanceEvent>,
+	}
+
+	impl TestInstance {
+		fn take(&mut self) -> Box<Instance> {
+			self.instance.take().unwrap()
+		}
+
+		fn state(&self) -> InstanceState {
+			self.state.load(Ordering::Relaxed)
+		}
+
+		fn events(&self) -> Vec<InstanceEvent> {
+			self.events.drain().collect()
+		}
+	}
+
+	/// The parts of the backend instances interact with.
+	struct TestBackend {
+		instances: Instances,
+		playables: Playables,
+		groups: Groups,
+		metronomes: Metronomes,
+		parameters: Parameters,
+		spatial_scene: SpatialScene,
+		unloader: Sender<Resource>,
+	}
+
+	impl TestBackend {
+		fn new(num_instances: usize) -> Self {
+			Self {
+				instances: Instances::new(num_instances, 0),
+				playables: Playables::new(10, 0),
+				groups: Groups::new(0),
+				metronomes: Metronomes::new(1),
+				parameters: Parameters::new(0),
+				spatial_scene: SpatialScene::new(0),
+				unloader: flume::unbounded().0,
+			}
+		}
+
+		/// Adds a silent sound that lasts 10 seconds.
+		///
+		/// The sound has no cooldown, so it can be played
+		/// multiple times in a row.
+		fn add_sound(&mut self, settings: SoundSettings) -> PlayableId {
+			let sound = Sound::from_frames(
+				SAMPLE_RATE,
+				vec![Frame::from_mono(0.0); SAMPLE_RATE as usize * 10],
+				SoundSettings {
+					cooldown: None,
+					..settings
+				},
+			);
+			let id = PlayableId::Sound(sound.id());
+			self.playables
+				.run_command(ResourceCommand::AddSound(sound), &mut self.unloader);
+			id
+		}
+
+		fn instance(&self, playable_id: PlayableId, settings: InstanceSettings) -> TestInstance {
+			let duration = self.playables.playable(playable_id).unwrap().duration();
+			let (event_sender, events) = flume::unbounded();
+			let instance = Instance::new(
+				playable_id,
+				duration,
+				None,
+				settings.into_internal(duration, None, TrackIndex::Main),
+				None,
+				Some(event_sender),
+			);
+			TestInstance {
+				id: instance.id(),
+				state: instance.public_state(),
+				instance: Some(Box::new(instance)),
+				events,
+			}
+		}
+
+		fn run_command(&mut self, command: InstanceCommand) {
+			self.instances.run_command(
+				command,
+				&mut self.playables,
+				&self.groups,
+				&mut self.unloader,
+			);
+		}
+
+		fn playing_instance(&self, instance: &TestInstance) -> &Instance {
+			self.instances.instances.get(&instance.id).unwrap()
+		}
+
+		/// Crossfades from a playing instance to a new one the
+		/// same way [`AudioManager::crossfade`](crate::manager::AudioManager::crossfade) does.
+		fn crossfade(
+			&mut self,
+			from: &TestInstance,
+			to: PlayableId,
+			settings: CrossfadeSettings,
+		) -> TestInstance {
+			let mut instance = self.instance(
+				to,
+				InstanceSettings::new().fade_in_tween(settings.fade_in_tween().unwrap()),
+			);
+			self.run_command(InstanceCommand::Crossfade(
+				from.id,
+				instance.id,
+				instance.take(),
+				settings.into_internal(),
+			));
+			instance
+		}
+
+		fn play(&mut self, instance: &mut TestInstance) {
+			let instance = instance.take();
+			self.run_command(InstanceCommand::Play(instance.id(), instance));
+		}
+
+		/// Advances time the same way the backend does.
+		fn update(&mut self, dt: f64) {
+			self.playables.update(dt);
+			self.metronomes.update(dt, &self.parameters);
+			self.instances.update_pending_commands(
+				dt,
+				&mut self.playables,
+				&self.groups,
+				&self.metronomes,
+				&mut self.unloader,
+			);
+			for (_, instance) in &mut self.instances.instances {
+				instance.update(dt, &self.playables, &self.parameters, &self.spatial_scene);
+			}
+		}
+	}
+
+	fn limited_sound(backend: &mut TestBackend, behavior: InstanceLimitBehavior) -> PlayableId {
+		backend.add_sound(
+			SoundSettings::new()
+				.max_instances(2)
+				.instance_limit_behavior(behavior),
+		)
+	}
+
+	#[test]
+	fn instance_limit_stops_oldest() {
+		let mut backend = TestBackend::new(10);
+		let sound = limited_sound(&mut backend, InstanceLimitBehavior::StopOldest);
+		let mut instances: Vec<_> = (0..4)
+			.map(|_| backend.instance(sound, InstanceSettings::new()))
+			.collect();
+		for instance in &mut instances[..3] {
+			backend.play(instance);
+		}
+		assert_eq!(
+			instances[0].events(),
+			[InstanceEvent::Started, InstanceEvent::Stolen]
+		);
+		assert_eq!(instances[0].state(), InstanceState::Stopping);
+		assert_eq!(instances[1].state(), InstanceState::Playing);
+		assert_eq!(instances[2].state(), InstanceState::Playing);
+		// instances that are fading out don't count toward the limit
+		backend.play(&mut instances[3]);
+		assert_eq!(instances[1].state(), InstanceState::Stopping);
+		assert_eq!(instances[2].state(), InstanceState::Playing);
+		assert_eq!(instances[3].state(), InstanceState::Playing);
+	}
+
+	#[test]
+	fn instance_limit_stops_quietest() {
+		let mut backend = TestBackend::new(10);
+		let sound = limited_sound(&mut backend, InstanceLimitBehavior::StopQuietest);
+		let mut loud = backend.instance(sound, InstanceSettings::new().volume(0.5));
+		let mut quiet = backend.instance(sound, InstanceSettings::new().volume(0.2));
+		let mut new = backend.instance(sound, InstanceSettings::new());
+		backend.play(&mut loud);
+		backend.play(&mut quiet);
+		backend.play(&mut new);
+		assert_eq!(loud.state(), InstanceState::Playing);
+		assert_eq!(quiet.state(), InstanceState::Stopping);
+		assert_eq!(new.state(), InstanceState::Playing);
+	}
+
+	#[test]
+	fn instance_limit_orders_volumes_totally() {
+		let mut backend = TestBackend::new(10);
+		let sound = limited_sound(&mut backend, InstanceLimitBehavior::StopQuietest);
+		// NaN volumes are louder than any other volume, and
+		// the older instance is stopped when volumes are equal
+		let mut nan = backend.instance(sound, InstanceSettings::new().volume(f64::NAN));
+		let mut first = backend.instance(sound, InstanceSettings::new());
+		let mut second = backend.instance(sound, InstanceSettings::new());
+		let mut third = backend.instance(sound, InstanceSettings::new());
+		backend.play(&mut nan);
+		backend.play(&mut first);
+		backend.play(&mut second);
+		assert_eq!(nan.state(), InstanceState::Playing);
+		assert_eq!(first.state(), InstanceState::Stopping);
+		backend.play(&mut third);
+		assert_eq!(nan.state(), InstanceState::Playing);
+		assert_eq!(second.state(), InstanceState::Stopping);
+		assert_eq!(third.state(), InstanceState::Playing);
+	}
+
+	#[test]
+	fn instance_limit_ignores_new() {
+		let mut backend = TestBackend::new(10);
+		let sound = limited_sound(&mut backend, InstanceLimitBehavior::IgnoreNew);
+		let mut instances: Vec<_> = (0..3)
+			.map(|_| backend.instance(sound, InstanceSettings::new()))
+			.collect();
+		for instance in &mut instances {
+			backend.play(instance);
+		}
+		assert_eq!(instances[0].state(), InstanceState::Playing);
+		assert_eq!(instances[1].state(), InstanceState::Playing);
+		assert_eq!(instances[2].state(), InstanceState::Stopped);
+		// rejected instances never start, so they don't emit events
+		assert_eq!(instances[2].events(), []);
+		assert_eq!(backend.instances.instances.len(), 2);
+	}
+
+	#[test]
+	fn pending_instances_steal_when_they_start() {
+		let mut backend = TestBackend::new(10);
+		let sound = backend.add_sound(SoundSettings::new().max_instances(1));
+		let mut playing = backend.instance(sound, InstanceSettings::new());
+		let mut pending = backend.instance(
+			sound,
+			InstanceSettings::new()
+				.start_timing(InstanceTiming::new().delay(Duration::Seconds(0.5))),
+		);
+		backend.play(&mut playing);
+		backend.play(&mut pending);
+		// instances that are waiting to start don't count
+		// toward the limit
+		assert_eq!(playing.state(), InstanceState::Playing);
+		assert_eq!(pending.state(), InstanceState::Pending);
+		backend.update(0.3);
+		assert_eq!(playing.state(), InstanceState::Playing);
+		backend.update(0.3);
+		assert_eq!(
+			playing.events(),
+			[
+				InstanceEvent::Started,
+				InstanceEvent::Stolen,
+				InstanceEvent::FadeFinished,
+				InstanceEvent::Stopped
+			]
+		);
+		assert_eq!(pending.events(), [InstanceEvent::Started]);
+		assert_eq!(pending.state(), InstanceState::Playing);
+	}
+
+	fn delayed(delay: f64) -> InstanceTiming {
+		InstanceTiming::new().delay(Duration::Seconds(delay))
+	}
+
+	#[test]
+	fn delayed_start() {
+		let mut backend = TestBackend::new(10);
+		let sound = backend.add_sound(SoundSettings::new());
+		let mut instance =
+			backend.instance(sound, InstanceSettings::new().start_timing(delayed(1.0)));
+		backend.play(&mut instance);
+		backend.update(0.6);
+		assert_eq!(instance.state(), InstanceState::Pending);
+		assert_eq!(instance.events(), []);
+		backend.update(0.6);
+		assert_eq!(instance.state(), InstanceState::Playing);
+		assert_eq!(instance.events(), [InstanceEvent::Started]);
+	}
+
+	#[test]
+	fn quantized_start() {
+		let mut backend = TestBackend::new(10);
+		let sound = backend.add_sound(SoundSettings::new());
+		let metronome = MetronomeId::new();
+		backend.metronomes.run_command(
+			MetronomeCommand::AddMetronome(
+				metronome,
+				Metronome::new(MetronomeSettings::new(), flume::unbounded().0),
+			),
+			&mut backend.unloader,
+		);
+		backend.metronomes.run_command(
+			MetronomeCommand::StartMetronome(metronome),
+			&mut backend.unloader,
+		);
+		const DT: f64 = 0.01;
+		// move the metronome 0.1 seconds past the first beat
+		for _ in 0..10 {
+			backend.update(DT);
+		}
+		let mut instance = backend.instance(
+			sound,
+			InstanceSettings::new().start_timing(InstanceTiming::new().quantize(metronome, 1.0)),
+		);
+		backend.play(&mut instance);
+		let mut steps = 0;
+		while instance.state() == InstanceState::Pending {
+			backend.update(DT);
+			steps += 1;
+			assert!(steps < 100, "the instance never started");
+		}
+		// at 120 BPM, the next beat is 0.4 seconds later
+		assert!((39..=41).contains(&steps), "{}", steps);
+	}
+
+	#[test]
+	fn waiting_commands_past_the_limit_are_dropped() {
+		let mut backend = TestBackend::new(3);
+		let sound = backend.add_sound(SoundSettings::new());
+		let mut playing = backend.instance(sound, InstanceSettings::new());
+		backend.play(&mut playing);
+		let mut pending: Vec<_> = (0..4)
+			.map(|_| backend.instance(sound, InstanceSettings::new().start_timing(delayed(5.0))))
+			.collect();
+		for instance in &mut pending {
+			backend.play(instance);
+		}
+		assert_eq!(backend.instances.pending_commands.len(), 3);
+		for instance in &pending[..3] {
+			assert_eq!(instance.state(), InstanceState::Pending);
+		}
+		// a dropped play never starts
+		assert_eq!(pending[3].state(), InstanceState::Stopped);
+		assert_eq!(pending[3].events(), []);
+		// a dropped stop never takes effect
+		backend.run_command(InstanceCommand::StopInstance(
+			playing.id,
+			StopInstanceSettings::new().timing(delayed(0.5)),
+		));
+		backend.update(1.0);
+		assert_eq!(playing.state(), InstanceState::Playing);
+	}
+
+	#[test]
+	fn commands_reach_pending_instances() {
+		let mut backend = TestBackend::new(10);
+		let sound = backend.add_sound(SoundSettings::new());
+		let mut instance =
+			backend.instance(sound, InstanceSettings::new().start_timing(delayed(1.0)));
+		backend.play(&mut instance);
+		backend.run_command(InstanceCommand::SetInstanceVolume(
+			instance.id,
+			0.5.into(),
+			None,
+		));
+		backend.run_command(InstanceCommand::SeekInstanceTo(instance.id, 2.0));
+		backend.update(1.1);
+		assert_eq!(instance.state(), InstanceState::Playing);
+		let playing_instance = backend.playing_instance(&instance);
+		assert_eq!(playing_instance.effective_volume(), 0.5);
+		// the instance starts from the new position and
+		// then plays for the rest of the update
+		assert!((playing_instance.position() - 3.1).abs() < 1e-9);
+	}
+
+	#[test]
+	fn stopping_pending_instances_cancels_them() {
+		let mut backend = TestBackend::new(10);
+		let sound = backend.add_sound(SoundSettings::new());
+		let mut instance =
+			backend.instance(sound, InstanceSettings::new().start_timing(delayed(1.0)));
+		backend.play(&mut instance);
+		backend.run_command(InstanceCommand::StopInstance(
+			instance.id,
+			StopInstanceSettings::new(),
+		));
+		assert!(backend.instances.pending_commands.is_empty());
+		assert_eq!(instance.state(), InstanceState::Stopped);
+		backend.update(1.1);
+		assert_eq!(instance.state(), InstanceState::Stopped);
+		assert_eq!(instance.events(), []);
+		assert!(backend.instances.instances.is_empty());
+	}
+
+	#[test]
+	fn crossfade_rejected_by_instance_limit_keeps_old_instance() {
+		let mut backend = TestBackend::new(10);
+		let old_sound = backend.add_sound(SoundSettings::new());
+		let new_sound = backend.add_sound(
+			SoundSettings::new()
+				.max_instances(1)
+				.instance_limit_behavior(InstanceLimitBehavior::IgnoreNew),
+		);
+		let mut old = backend.instance(old_sound, InstanceSettings::new());
+		let mut other = backend.instance(new_sound, InstanceSettings::new());
+		backend.play(&mut old);
+		backend.play(&mut other);
+		let new = backend.crossfade(&old, new_sound, CrossfadeSettings::new());
+		assert_eq!(new.state(), InstanceState::Stopped);
+		assert_eq!(new.events(), []);
+		backend.update(2.0);
+		assert_eq!(old.state(), InstanceState::Playing);
+		assert_eq!(old.events(), [InstanceEvent::Started]);
+		assert_eq!(backend.playing_instance(&old).effective_volume(), 1.0);
+		assert_eq!(other.state(), InstanceState::Playing);
+	}
+
+	#[test]
+	fn crossfade_replaces_instance_at_limit() {
+		let mut backend = TestBackend::new(10);
+		let sound = backend.add_sound(
+			SoundSettings::new()
+				.max_instances(1)
+				.instance_limit_behavior(InstanceLimitBehavior::IgnoreNew),
+		);
+		let mut old = backend.instance(sound, InstanceSettings::new());
+		backend.play(&mut old);
+		// the old instance doesn't count toward the limit,
+		// since it's being replaced
+		let new = backend.crossfade(&old, sound, CrossfadeSettings::new());
+		assert_eq!(new.state(), InstanceState::Playing);
+		assert_eq!(old.state(), InstanceState::Stopping);
+	}
+
+	#[test]
+	fn crossfade_is_equal_power() {
+		let mut backend = TestBackend::new(10);
+		let sound = backend.add_sound(SoundSettings::new());
+		let mut old = backend.instance(sound, InstanceSettings::new());
+		backend.play(&mut old);
+		let new = backend.crossfade(&old, sound, CrossfadeSettings::new().duration(1.0));
+		for _ in 0..110 {
+			backend.update(0.01);
+			let old_volume = backend.playing_instance(&old).effective_volume();
+			let new_volume = backend.playing_instance(&new).effective_volume();
+			let power = old_volume.powi(2) + new_volume.powi(2);
+			assert!((power - 1.0).abs() < 1e-9, "{}", power);
 		}
+		assert_eq!(old.state(), InstanceState::Stopped);
+		assert_eq!(backend.playing_instance(&new).effective_volume(), 1.0);
 	}
 }
@@ -8,7 +8,7 @@ use self::mixer::Mixer;
 use super::AudioManagerSettings;
 use crate::{
 	command::Command, frame::Frame, group::groups::Groups, metronome::Metronomes,
-	parameter::Parameters, playable::Playables, resource::Resource,
+	parameter::Parameters, playable::Playables, resource::Resource, spatial::SpatialScene,
 };
 use flume::{Receiver, Sender};
 use instances::Instances;
@@ -29,6 +29,7 @@ pub struct Backend {
 	mixer: Mixer,
 	groups: Groups,
 	streams: Streams,
+	spatial_scene: SpatialScene,
 }
 
 impl Backend {
@@ -46,11 +47,12 @@ impl Backend {
 			unloader,
 			parameters: Parameters::new(settings.num_parameters),
 			metronomes: Metronomes::new(settings.num_metronomes),
-			instances: Instances::new(settings.num_instances),
+			instances: Instances::new(settings.num_instances, settings.num_binaural_instances),
 			sequences: Sequences::new(settings.num_sequences, settings.num_commands),
 			mixer: Mixer::new(),
 			groups: Groups::new(settings.num_groups),
 			streams: Streams::new(settings.num_streams),
+			spatial_scene: SpatialScene::new(settings.num_emitters),
 		}
 	}
 
@@ -65,8 +67,12 @@ impl Backend {
 					self.metronomes.run_command(command, &mut self.unloader);
 				}
 				Command::Instance(command) => {
-					self.instances
-						.run_command(command, &mut self.playables, &self.groups);
+					self.instances.run_command(
+						command,
+						&mut self.playables,
+						&self.groups,
+						&mut self.unloader,
+					);
 				}
 				Command::Sequence(command) => {
 					self.sequences
@@ -84,6 +90,9 @@ impl Backend {
 				Command::Stream(command) => {
 					self.streams.run_command(command, &mut self.unloader);
 				}
+				Command::Spatial(command) => {
+					self.spatial_scene.run_command(command, &mut self.unloader);
+				}
 			}
 		}
 	}
@@ -105,10 +114,25 @@ impl Backend {
 		self.parameters.update(self.dt);
 		self.playables.update(self.dt);
 		self.metronomes.update(self.dt, &self.parameters);
+		self.spatial_scene.update(self.dt);
+		self.instances.update_tempo_ratios(&self.metronomes);
+		self.instances.update_pending_commands(
+			self.dt,
+			&mut self.playables,
+			&self.groups,
+			&self.metronomes,
+			&mut self.unloader,
+		);
 		self.update_sequences();
 		self.streams.process(self.dt, &mut self.mixer);
-		self.instances
-			.process(self.dt, &self.playables, &mut self.mixer, &self.parameters);
+		self.instances.process(
+			self.dt,
+			&self.playables,
+			&mut self.mixer,
+			&self.parameters,
+			&self.spatial_scene,
+			&mut self.unloader,
+		);
 		self.mixer.process(self.dt, &self.parameters)
 	}
 }
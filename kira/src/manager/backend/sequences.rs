@@ -1,11 +1,11 @@
 use crate::{
 	command::{Command, InstanceCommand, MetronomeCommand, ParameterCommand, SequenceCommand},
 	group::groups::Groups,
-	instance::Instance,
+	instance::{Instance, InstanceSettings},
 	metronome::Metronomes,
 	playable::Playables,
 	resource::Resource,
-	sequence::{SequenceInstance, SequenceInstanceId, SequenceOutputCommand},
+	sequence::{SequenceInstance, SequenceInstanceId, SequenceOutput, SequenceOutputCommand},
 };
 use flume::Sender;
 use indexmap::IndexMap;
@@ -14,7 +14,7 @@ use std::vec::Drain;
 pub(crate) struct Sequences {
 	sequence_instances: IndexMap<SequenceInstanceId, SequenceInstance>,
 	sequence_instances_to_remove: Vec<SequenceInstanceId>,
-	sequence_output_command_queue: Vec<SequenceOutputCommand>,
+	sequence_output_queue: Vec<SequenceOutput>,
 	output_command_queue: Vec<Command>,
 }
 
@@ -23,7 +23,7 @@ impl Sequences {
 		Self {
 			sequence_instances: IndexMap::with_capacity(sequence_capacity),
 			sequence_instances_to_remove: Vec::with_capacity(sequence_capacity),
-			sequence_output_command_queue: Vec::with_capacity(command_capacity),
+			sequence_output_queue: Vec::with_capacity(command_capacity),
 			output_command_queue: Vec::with_capacity(command_capacity),
 		}
 	}
@@ -107,18 +107,23 @@ impl Sequences {
 	) -> Drain<Command> {
 		// update sequences and process their commands
 		for (id, sequence_instance) in &mut self.sequence_instances {
-			sequence_instance.update(dt, metronomes, &mut self.sequence_output_command_queue);
+			sequence_instance.update(dt, metronomes, &mut self.sequence_output_queue);
 			// convert sequence commands to commands that can be consumed
 			// by the backend
-			for command in self.sequence_output_command_queue.drain(..) {
-				match command {
-					SequenceOutputCommand::PlaySound(playable_id, settings) => {
-						if let Some(playable) = playables.playable(playable_id) {
-							let instance_id = settings.id;
+			for output in self.sequence_output_queue.drain(..) {
+				let command = match output {
+					SequenceOutput::PlaySound(playable_id, step, instance_id) => {
+						let playable = playables.playable(playable_id);
+						let settings = sequence_instance.instance_settings(step);
+						if let (Some(playable), Some(settings)) = (playable, settings) {
+							let settings = InstanceSettings {
+								id: instance_id,
+								..*settings
+							};
 							self.output_command_queue.push(Command::Instance(
 								InstanceCommand::Play(
 									instance_id,
-									Instance::new(
+									Box::new(Instance::new(
 										playable_id,
 										playable.duration(),
 										Some(*id),
@@ -127,24 +132,30 @@ impl Sequences {
 											playable.default_loop_start(),
 											playable.default_track(),
 										),
-									),
+										None,
+										None,
+									)),
 								),
-							))
+							));
 						}
+						continue;
 					}
-					SequenceOutputCommand::SetInstanceVolume(id, volume) => {
+					SequenceOutput::RunCommand(command) => command,
+				};
+				match command {
+					SequenceOutputCommand::SetInstanceVolume(id, volume, tween) => {
 						self.output_command_queue.push(Command::Instance(
-							InstanceCommand::SetInstanceVolume(id, volume),
+							InstanceCommand::SetInstanceVolume(id, volume, tween),
 						))
 					}
-					SequenceOutputCommand::SetInstancePitch(id, pitch) => {
+					SequenceOutputCommand::SetInstancePitch(id, pitch, tween) => {
 						self.output_command_queue.push(Command::Instance(
-							InstanceCommand::SetInstancePitch(id, pitch),
+							InstanceCommand::SetInstancePitch(id, pitch, tween),
 						))
 					}
-					SequenceOutputCommand::SetInstancePanning(id, panning) => {
+					SequenceOutputCommand::SetInstancePanning(id, panning, tween) => {
 						self.output_command_queue.push(Command::Instance(
-							InstanceCommand::SetInstancePanning(id, panning),
+							InstanceCommand::SetInstancePanning(id, panning, tween),
 						))
 					}
 					SequenceOutputCommand::PauseInstance(id, settings) => {
@@ -10,8 +10,10 @@ use crate::{
 	metronome::MetronomeId,
 	mixer::{SubTrackId, TrackIndex},
 	parameter::ParameterId,
+	playable::PlayableId,
 	sequence::error::SequenceError,
 	sound::{error::SoundFromFileError, SoundId},
+	spatial::EmitterId,
 };
 
 /// Things that can go wrong when creating an `AudioManager`.
@@ -67,6 +69,62 @@ pub enum LoadSoundError {
 	SoundFromFileError(#[from] SoundFromFileError),
 }
 
+/// Things that can go wrong when loading a sound bank.
+#[cfg(all(
+	feature = "serde_support",
+	any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav")
+))]
+#[derive(Debug, Error)]
+pub enum LoadSoundBankError {
+	/// More than one sound in the bank has the same name.
+	#[error("More than one sound in the bank is named \"{0}\"")]
+	DuplicateName(String),
+
+	/// A sound refers to a track name that isn't defined.
+	#[error("The sound \"{sound}\" refers to a track named \"{track}\", which does not exist")]
+	NoTrackWithName {
+		/// The name of the sound.
+		sound: String,
+		/// The name of the track.
+		track: String,
+	},
+
+	/// A sound refers to a group name that isn't defined.
+	#[error("The sound \"{sound}\" refers to a group named \"{group}\", which does not exist")]
+	NoGroupWithName {
+		/// The name of the sound.
+		sound: String,
+		/// The name of the group.
+		group: String,
+	},
+
+	/// An error occurred when loading a sound.
+	#[error("Could not load the sound \"{sound}\": {error}")]
+	LoadSoundError {
+		/// The name of the sound.
+		sound: String,
+		/// The error that occurred.
+		#[source]
+		error: LoadSoundError,
+	},
+}
+
+/// Things that can go wrong when reloading a sound from a file.
+#[derive(Debug, Error)]
+pub enum ReloadSoundError {
+	/// No sound with the specified ID exists.
+	#[error("The sound with the specified ID does not exist")]
+	NoSoundWithId(SoundId),
+
+	/// An error occurred when loading the sound from a file.
+	#[error("{0}")]
+	SoundFromFileError(#[from] SoundFromFileError),
+
+	/// The audio thread has finished and can no longer receive commands.
+	#[error("The backend cannot receive commands because it no longer exists")]
+	BackendDisconnected,
+}
+
 /// Things that can go wrong when removing a sound from the
 /// audio thread.
 #[derive(Debug, Error)]
@@ -138,6 +196,31 @@ pub enum RemoveMetronomeError {
 	BackendDisconnected,
 }
 
+/// Things that can go wrong when adding an emitter to the audio thread.
+#[derive(Debug, Error)]
+pub enum AddEmitterError {
+	/// The maximum emitter limit has been reached.
+	#[error("Cannot add an emitter because the max number of emitters has been reached")]
+	EmitterLimitReached,
+
+	/// The audio thread has finished and can no longer receive commands.
+	#[error("The backend cannot receive commands because it no longer exists")]
+	BackendDisconnected,
+}
+
+/// Things that can go wrong when removing an emitter from the
+/// audio thread.
+#[derive(Debug, Error)]
+pub enum RemoveEmitterError {
+	/// No emitter with the specified ID exists.
+	#[error("The emitter with the specified ID does not exist")]
+	NoEmitterWithId(EmitterId),
+
+	/// The audio thread has finished and can no longer receive commands.
+	#[error("The backend cannot receive commands because it no longer exists")]
+	BackendDisconnected,
+}
+
 /// Things that can go wrong when adding a group to the audio thread.
 #[derive(Debug, Error)]
 pub enum AddGroupError {
@@ -265,3 +348,15 @@ pub enum StartSequenceError {
 	#[error("The backend cannot receive commands because it no longer exists")]
 	BackendDisconnected,
 }
+
+/// Things that can go wrong when crossfading between instances.
+#[derive(Debug, Error)]
+pub enum CrossfadeError {
+	/// No sound or arrangement with the specified ID exists.
+	#[error("The sound or arrangement with the specified ID does not exist")]
+	NoPlayableWithId(PlayableId),
+
+	/// The audio thread has finished and can no longer receive commands.
+	#[error("The backend cannot receive commands because it no longer exists")]
+	BackendDisconnected,
+}
@@ -3,36 +3,53 @@
 mod active_ids;
 mod backend;
 pub mod error;
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+mod sound_watcher;
 
-use std::hash::Hash;
+use std::{collections::HashMap, hash::Hash};
 
 use active_ids::ActiveIds;
 #[cfg(not(feature = "benchmarking"))]
 use backend::Backend;
 #[cfg(feature = "benchmarking")]
 pub use backend::Backend;
+#[cfg(all(
+	feature = "serde_support",
+	any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav")
+))]
+use error::LoadSoundBankError;
 use error::{
-	AddArrangementError, AddGroupError, AddMetronomeError, AddParameterError, AddSoundError,
-	AddStreamError, AddTrackError, LoadSoundError, RemoveArrangementError, RemoveGroupError,
-	RemoveMetronomeError, RemoveParameterError, RemoveSoundError, RemoveStreamError,
-	RemoveTrackError, SetupError, StartSequenceError,
+	AddArrangementError, AddEmitterError, AddGroupError, AddMetronomeError, AddParameterError,
+	AddSoundError, AddStreamError, AddTrackError, CrossfadeError, RemoveArrangementError,
+	RemoveEmitterError, RemoveGroupError, RemoveMetronomeError, RemoveParameterError,
+	RemoveSoundError, RemoveStreamError, RemoveTrackError, SetupError, StartSequenceError,
 };
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+use error::{LoadSoundError, ReloadSoundError};
 use flume::{Receiver, Sender};
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+pub use sound_watcher::SoundWatcher;
 
 use crate::{
 	arrangement::{handle::ArrangementHandle, Arrangement, ArrangementId},
 	audio_stream::{AudioStream, AudioStreamId},
 	command::{
-		Command, GroupCommand, MetronomeCommand, MixerCommand, ParameterCommand, ResourceCommand,
-		SequenceCommand, StreamCommand,
+		Command, GroupCommand, InstanceCommand, MetronomeCommand, MixerCommand, ParameterCommand,
+		ResourceCommand, SequenceCommand, SpatialCommand, StreamCommand,
 	},
 	group::{handle::GroupHandle, Group, GroupId, GroupSet, GroupSettings},
+	instance::{handle::InstanceHandle, CrossfadeSettings, InstanceId, InstanceSettings},
 	metronome::{handle::MetronomeHandle, Metronome, MetronomeId, MetronomeSettings},
 	mixer::{handle::TrackHandle, SubTrackId, Track, TrackIndex, TrackSettings},
 	parameter::{handle::ParameterHandle, ParameterId, ParameterSettings},
+	playable::PlayableId,
 	resource::Resource,
 	sequence::{handle::SequenceInstanceHandle, Sequence, SequenceInstanceSettings},
-	sound::{handle::SoundHandle, Sound, SoundId},
+	sound::{handle::SoundHandle, LoadSettings, Sound, SoundId},
+	spatial::{
+		handle::{EmitterHandle, ListenerHandle},
+		Emitter, EmitterId, EmitterSettings,
+	},
 };
 use cpal::{
 	traits::{DeviceTrait, HostTrait, StreamTrait},
@@ -72,6 +89,12 @@ pub struct AudioManagerSettings {
 	pub num_streams: usize,
 	/// The maximum number of metronomes that can be used at a time.
 	pub num_metronomes: usize,
+	/// The maximum number of emitters that can be used at a time.
+	pub num_emitters: usize,
+	/// The maximum number of instances that can be rendered
+	/// binaurally at a time. Other instances attached to
+	/// binaural emitters are panned instead.
+	pub num_binaural_instances: usize,
 }
 
 impl Default for AudioManagerSettings {
@@ -87,6 +110,8 @@ impl Default for AudioManagerSettings {
 			num_groups: 100,
 			num_streams: 10,
 			num_metronomes: 5,
+			num_emitters: 100,
+			num_binaural_instances: 16,
 		}
 	}
 }
@@ -102,6 +127,9 @@ pub struct AudioManager {
 	command_sender: Sender<Command>,
 	resources_to_unload_receiver: Receiver<Resource>,
 	active_ids: ActiveIds,
+	sound_load_settings: HashMap<SoundId, LoadSettings>,
+	sound_handles: HashMap<SoundId, SoundHandle>,
+	arrangement_handles: HashMap<ArrangementId, ArrangementHandle>,
 
 	// on wasm, holds the stream (as it has been created on the main thread)
 	// so it can live for as long as the audio manager
@@ -158,6 +186,9 @@ impl AudioManager {
 			quit_signal_sender,
 			command_sender,
 			active_ids,
+			sound_load_settings: HashMap::new(),
+			sound_handles: HashMap::new(),
+			arrangement_handles: HashMap::new(),
 			resources_to_unload_receiver,
 		})
 	}
@@ -173,6 +204,9 @@ impl AudioManager {
 			quit_signal_sender,
 			command_sender,
 			active_ids,
+			sound_load_settings: HashMap::new(),
+			sound_handles: HashMap::new(),
+			arrangement_handles: HashMap::new(),
 			resources_to_unload_receiver,
 			_stream: Self::setup_stream(settings, command_receiver, unloader)?,
 		})
@@ -225,6 +259,9 @@ impl AudioManager {
 			quit_signal_sender,
 			command_sender,
 			active_ids: ActiveIds::new(&settings),
+			sound_load_settings: HashMap::new(),
+			sound_handles: HashMap::new(),
+			arrangement_handles: HashMap::new(),
 			resources_to_unload_receiver,
 		};
 		let backend = Backend::new(SAMPLE_RATE, settings, command_receiver, unloader);
@@ -246,7 +283,7 @@ impl AudioManager {
 	}
 
 	/// Sends a sound to the audio thread and returns a handle to the sound.
-	pub fn add_sound(&mut self, sound: Sound) -> Result<SoundHandle, AddSoundError> {
+	pub fn add_sound(&mut self, mut sound: Sound) -> Result<SoundHandle, AddSoundError> {
 		if !self.does_track_exist(sound.default_track()) {
 			return Err(AddSoundError::NoTrackWithIndex(sound.default_track()));
 		}
@@ -255,6 +292,10 @@ impl AudioManager {
 		}
 		self.active_ids.add_sound_id(sound.id())?;
 		let handle = SoundHandle::new(&sound, self.command_sender.clone());
+		sound.share_duration(handle.shared_duration());
+		self.sound_load_settings
+			.insert(sound.id(), sound.load_settings());
+		self.sound_handles.insert(sound.id(), handle.clone());
 		self.command_sender
 			.send(ResourceCommand::AddSound(sound).into())
 			.map_err(|_| AddSoundError::BackendDisconnected)?;
@@ -275,10 +316,141 @@ impl AudioManager {
 		Ok(self.add_sound(sound)?)
 	}
 
+	/// Loads every sound in a sound bank manifest and returns
+	/// a [`SoundBank`](crate::sound::bank::SoundBank) that can
+	/// be used to look up the sounds by name.
+	///
+	/// The paths of the sounds are relative to `directory`,
+	/// and the track and group names they use are looked up
+	/// in `names`. Each sound is given a new ID. If any sound
+	/// fails to load, the sounds that were already loaded
+	/// are removed.
+	#[cfg(all(
+		feature = "serde_support",
+		any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav")
+	))]
+	pub fn load_sound_bank(
+		&mut self,
+		manifest: &crate::sound::bank::SoundBankManifest,
+		directory: impl AsRef<std::path::Path>,
+		names: &crate::sound::bank::SoundBankNames,
+	) -> Result<crate::sound::bank::SoundBank, LoadSoundBankError> {
+		for (i, entry) in manifest.sounds.iter().enumerate() {
+			if manifest.sounds[..i]
+				.iter()
+				.any(|other| other.name == entry.name)
+			{
+				return Err(LoadSoundBankError::DuplicateName(entry.name.clone()));
+			}
+		}
+		let mut bank = crate::sound::bank::SoundBank::default();
+		for entry in &manifest.sounds {
+			match self.load_sound_bank_entry(entry, directory.as_ref(), names) {
+				Ok(handle) => {
+					bank.sounds.insert(entry.name.clone(), handle);
+				}
+				Err(error) => {
+					self.unload_sound_bank(bank).ok();
+					return Err(error);
+				}
+			}
+		}
+		Ok(bank)
+	}
+
+	#[cfg(all(
+		feature = "serde_support",
+		any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav")
+	))]
+	fn load_sound_bank_entry(
+		&mut self,
+		entry: &crate::sound::bank::SoundBankEntry,
+		directory: &std::path::Path,
+		names: &crate::sound::bank::SoundBankNames,
+	) -> Result<SoundHandle, LoadSoundBankError> {
+		let mut settings = entry.settings.clone().id(SoundId::new());
+		if let Some(track) = &entry.track {
+			settings.default_track =
+				*names
+					.tracks
+					.get(track)
+					.ok_or_else(|| LoadSoundBankError::NoTrackWithName {
+						sound: entry.name.clone(),
+						track: track.clone(),
+					})?;
+		}
+		if !entry.groups.is_empty() {
+			let mut groups = GroupSet::new();
+			for group in &entry.groups {
+				groups = groups.add(*names.groups.get(group).ok_or_else(|| {
+					LoadSoundBankError::NoGroupWithName {
+						sound: entry.name.clone(),
+						group: group.clone(),
+					}
+				})?);
+			}
+			settings.groups = groups;
+		}
+		self.load_sound(directory.join(&entry.path), settings)
+			.map_err(|error| LoadSoundBankError::LoadSoundError {
+				sound: entry.name.clone(),
+				error,
+			})
+	}
+
+	/// Removes every sound in a sound bank from the audio thread.
+	#[cfg(all(
+		feature = "serde_support",
+		any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav")
+	))]
+	pub fn unload_sound_bank(
+		&mut self,
+		bank: crate::sound::bank::SoundBank,
+	) -> Result<(), RemoveSoundError> {
+		for (_, handle) in bank.sounds {
+			self.remove_sound(handle.id())?;
+		}
+		Ok(())
+	}
+
+	/// Replaces the audio of a sound with audio loaded from a file.
+	///
+	/// The sound keeps its ID and settings, and the file is decoded
+	/// the same way the sound's audio originally was (using the
+	/// same channel policy, sample format, and so on). Instances
+	/// of the sound keep playing using the new audio, and their
+	/// playback positions are moved to the end of the new audio
+	/// if it's shorter than their current positions. Arrangements
+	/// using the sound will also use the new audio.
+	#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+	pub fn reload_sound(
+		&mut self,
+		id: impl Into<SoundId>,
+		path: impl AsRef<std::path::Path>,
+	) -> Result<(), ReloadSoundError> {
+		let id = id.into();
+		let load_settings = *self
+			.sound_load_settings
+			.get(&id)
+			.ok_or(ReloadSoundError::NoSoundWithId(id))?;
+		let settings = crate::sound::SoundSettings::new()
+			.id(id)
+			.with_load_settings(load_settings);
+		let sound = Sound::from_file(path, settings)?;
+		if let Some(handle) = self.sound_handles.get(&id) {
+			handle.set_waveform_source(sound.waveform_source());
+		}
+		self.command_sender
+			.send(ResourceCommand::ReloadSound(sound).into())
+			.map_err(|_| ReloadSoundError::BackendDisconnected)
+	}
+
 	/// Removes a sound from the audio thread.
 	pub fn remove_sound(&mut self, id: impl Into<SoundId>) -> Result<(), RemoveSoundError> {
 		let id = id.into();
 		self.active_ids.remove_sound_id(id)?;
+		self.sound_load_settings.remove(&id);
+		self.sound_handles.remove(&id);
 		self.command_sender
 			.send(ResourceCommand::RemoveSound(id).into())
 			.map_err(|_| RemoveSoundError::BackendDisconnected)
@@ -299,6 +471,8 @@ impl AudioManager {
 		}
 		self.active_ids.add_arrangement_id(arrangement.id())?;
 		let handle = ArrangementHandle::new(&arrangement, self.command_sender.clone());
+		self.arrangement_handles
+			.insert(arrangement.id(), handle.clone());
 		self.command_sender
 			.send(ResourceCommand::AddArrangement(arrangement).into())
 			.map_err(|_| AddArrangementError::BackendDisconnected)?;
@@ -312,11 +486,57 @@ impl AudioManager {
 	) -> Result<(), RemoveArrangementError> {
 		let id = id.into();
 		self.active_ids.remove_arrangement_id(id)?;
+		self.arrangement_handles.remove(&id);
 		self.command_sender
 			.send(ResourceCommand::RemoveArrangement(id.into()).into())
 			.map_err(|_| RemoveArrangementError::BackendDisconnected)
 	}
 
+	/// Starts a new instance of a sound or arrangement and
+	/// crossfades to it from an existing instance.
+	///
+	/// The old instance fades out and stops while the new instance
+	/// fades in. Both fades use equal-power curves, so the overall
+	/// volume stays steady when the two instances aren't correlated.
+	/// If the old instance doesn't exist (for example, because it
+	/// already finished), the new instance just fades in.
+	pub fn crossfade(
+		&mut self,
+		from: InstanceId,
+		to: impl Into<PlayableId>,
+		settings: CrossfadeSettings,
+	) -> Result<InstanceHandle, CrossfadeError> {
+		let to = to.into();
+		let instance_settings = InstanceSettings {
+			fade_in_tween: settings.fade_in_tween(),
+			start_timing: settings.timing,
+			..settings.instance_settings
+		};
+		let (instance, handle) = match to {
+			PlayableId::Sound(id) => self
+				.sound_handles
+				.get(&id)
+				.map(|sound| sound.create_instance(instance_settings)),
+			PlayableId::Arrangement(id) => self
+				.arrangement_handles
+				.get(&id)
+				.map(|arrangement| arrangement.create_instance(instance_settings)),
+		}
+		.ok_or(CrossfadeError::NoPlayableWithId(to))?;
+		self.command_sender
+			.send(
+				InstanceCommand::Crossfade(
+					from,
+					handle.id(),
+					Box::new(instance),
+					settings.into_internal(),
+				)
+				.into(),
+			)
+			.map_err(|_| CrossfadeError::BackendDisconnected)?;
+		Ok(handle)
+	}
+
 	/// Frees resources that are no longer in use, such as unloaded sounds
 	/// or finished sequences.
 	pub fn free_unused_resources(&mut self) {
@@ -353,6 +573,37 @@ impl AudioManager {
 			.map_err(|_| RemoveMetronomeError::BackendDisconnected)
 	}
 
+	/// Returns a handle to the listener, which controls
+	/// where spatial sounds are heard from.
+	pub fn listener(&self) -> ListenerHandle {
+		ListenerHandle::new(self.command_sender.clone())
+	}
+
+	/// Adds an emitter and returns a handle to it.
+	pub fn add_emitter(
+		&mut self,
+		settings: EmitterSettings,
+	) -> Result<EmitterHandle, AddEmitterError> {
+		let id = settings.id;
+		self.active_ids.add_emitter_id(id)?;
+		self.command_sender
+			.send(SpatialCommand::AddEmitter(id, Box::new(Emitter::new(settings))).into())
+			.map_err(|_| AddEmitterError::BackendDisconnected)?;
+		Ok(EmitterHandle::new(id, self.command_sender.clone()))
+	}
+
+	/// Removes an emitter from the audio thread.
+	///
+	/// Instances attached to the emitter keep playing, but
+	/// they're no longer spatialized.
+	pub fn remove_emitter(&mut self, id: impl Into<EmitterId>) -> Result<(), RemoveEmitterError> {
+		let id = id.into();
+		self.active_ids.remove_emitter_id(id)?;
+		self.command_sender
+			.send(SpatialCommand::RemoveEmitter(id).into())
+			.map_err(|_| RemoveEmitterError::BackendDisconnected)
+	}
+
 	/// Starts a sequence.
 	pub fn start_sequence<CustomEvent: Clone + Eq + Hash>(
 		&mut self,
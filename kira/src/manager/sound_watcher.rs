@@ -0,0 +1,77 @@
+use std::{
+	path::{Path, PathBuf},
+	time::SystemTime,
+};
+
+use crate::sound::SoundId;
+
+use super::{error::ReloadSoundError, AudioManager};
+
+#[derive(Debug, Clone)]
+struct WatchedFile {
+	sound_id: SoundId,
+	path: PathBuf,
+	modified: Option<SystemTime>,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+	std::fs::metadata(path)
+		.and_then(|metadata| metadata.modified())
+		.ok()
+}
+
+/// Reloads sounds when the audio files they were loaded
+/// from change.
+///
+/// The watcher checks the modification time of each file
+/// when [`SoundWatcher::update`] is called, so you should
+/// call it periodically, like once per frame.
+#[derive(Debug, Clone, Default)]
+pub struct SoundWatcher {
+	files: Vec<WatchedFile>,
+}
+
+impl SoundWatcher {
+	/// Creates a new sound watcher that isn't watching any files.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Starts watching a file. When the file changes, the
+	/// sound with the given ID will be reloaded from it.
+	pub fn watch(&mut self, id: impl Into<SoundId>, path: impl AsRef<Path>) {
+		let path = path.as_ref().to_path_buf();
+		self.files.push(WatchedFile {
+			sound_id: id.into(),
+			modified: modified_time(&path),
+			path,
+		});
+	}
+
+	/// Stops watching the files for the sound with the given ID.
+	pub fn unwatch(&mut self, id: impl Into<SoundId>) {
+		let id = id.into();
+		self.files.retain(|file| file.sound_id != id);
+	}
+
+	/// Reloads the sounds whose files have changed since the
+	/// last update, returning the ID of each reloaded sound
+	/// along with the result of reloading it.
+	pub fn update(
+		&mut self,
+		audio_manager: &mut AudioManager,
+	) -> Vec<(SoundId, Result<(), ReloadSoundError>)> {
+		let mut results = vec![];
+		for file in &mut self.files {
+			let modified = modified_time(&file.path);
+			if modified.is_some() && modified != file.modified {
+				file.modified = modified;
+				results.push((
+					file.sound_id,
+					audio_manager.reload_sound(file.sound_id, &file.path),
+				));
+			}
+		}
+		results
+	}
+}
@@ -0,0 +1,28 @@
+/// A named point in time in a sound or arrangement.
+///
+/// When the playback position of an instance passes a marker,
+/// the instance will emit an event that can be received
+/// from its [`InstanceHandle`](crate::instance::handle::InstanceHandle).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct Marker {
+	/// The position of the marker (in seconds).
+	///
+	/// Instances never pass markers whose position is NaN.
+	pub position: f64,
+	/// The name of the marker.
+	pub name: String,
+}
+
+impl Marker {
+	/// Creates a new marker at the given position (in seconds).
+	pub fn new(position: f64, name: impl Into<String>) -> Self {
+		Self {
+			position,
+			name: name.into(),
+		}
+	}
+}
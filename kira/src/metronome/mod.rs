@@ -62,6 +62,10 @@ impl Metronome {
 		}
 	}
 
+	pub fn tempo(&self) -> Tempo {
+		self.tempo.value()
+	}
+
 	pub fn effective_tempo(&self) -> Tempo {
 		if self.ticking {
 			self.tempo.value()
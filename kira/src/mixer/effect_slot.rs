@@ -16,7 +16,7 @@ impl EffectSlot {
 		}
 	}
 
-	pub(super) fn process(&mut self, dt: f64, input: Frame, parameters: &Parameters) -> Frame {
+	pub(crate) fn process(&mut self, dt: f64, input: Frame, parameters: &Parameters) -> Frame {
 		if self.enabled {
 			self.effect.process(dt, input, parameters)
 		} else {
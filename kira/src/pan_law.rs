@@ -0,0 +1,102 @@
+/// How the volume of each channel changes as audio is panned.
+///
+/// Pan laws differ in how loud audio is when it's panned to
+/// the center compared to when it's panned hard left or right.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum PanLaw {
+	/// Keeps the total power the same at every panning,
+	/// so the center is 3 dB quieter than a hard pan.
+	ConstantPower,
+	/// A compromise between constant power and linear
+	/// panning, with the center 4.5 dB quieter than a hard pan.
+	MinusFourPointFiveDb,
+	/// Scales each channel linearly, so the center is 6 dB
+	/// quieter than a hard pan.
+	Linear,
+	/// Constant power panning boosted so the center is at
+	/// full volume, with each channel's volume capped at 1.
+	ZeroDbCenter,
+}
+
+impl Default for PanLaw {
+	fn default() -> Self {
+		Self::ConstantPower
+	}
+}
+
+impl PanLaw {
+	/// Returns the volume of the left and right channels
+	/// for a panning from 0 (hard left) to 1 (hard right).
+	pub fn gains(self, panning: f32) -> (f32, f32) {
+		let right = panning.clamp(0.0, 1.0);
+		let left = 1.0 - right;
+		match self {
+			PanLaw::ConstantPower => (left.sqrt(), right.sqrt()),
+			PanLaw::MinusFourPointFiveDb => (left.powf(0.75), right.powf(0.75)),
+			PanLaw::Linear => (left, right),
+			PanLaw::ZeroDbCenter => ((2.0 * left).sqrt().min(1.0), (2.0 * right).sqrt().min(1.0)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_gains(law: PanLaw, panning: f32, expected: (f32, f32)) {
+		let (left, right) = law.gains(panning);
+		assert!(
+			(left - expected.0).abs() < 1e-6 && (right - expected.1).abs() < 1e-6,
+			"{:?} at {}: expected {:?}, got {:?}",
+			law,
+			panning,
+			expected,
+			(left, right)
+		);
+	}
+
+	#[test]
+	fn hard_pans_silence_the_opposite_channel() {
+		for &law in &[
+			PanLaw::ConstantPower,
+			PanLaw::MinusFourPointFiveDb,
+			PanLaw::Linear,
+			PanLaw::ZeroDbCenter,
+		] {
+			assert_gains(law, 0.0, (1.0, 0.0));
+			assert_gains(law, 1.0, (0.0, 1.0));
+		}
+	}
+
+	#[test]
+	fn center_gains() {
+		let db = |db: f32| 10.0f32.powf(db / 20.0);
+		// the center levels are approximately -3, -4.5, -6, and 0 dB
+		assert_gains(PanLaw::ConstantPower, 0.5, (0.5f32.sqrt(), 0.5f32.sqrt()));
+		assert_gains(
+			PanLaw::MinusFourPointFiveDb,
+			0.5,
+			(0.5f32.powf(0.75), 0.5f32.powf(0.75)),
+		);
+		assert_gains(PanLaw::Linear, 0.5, (0.5, 0.5));
+		assert_gains(PanLaw::ZeroDbCenter, 0.5, (1.0, 1.0));
+		assert!((PanLaw::ConstantPower.gains(0.5).0 - db(-3.0)).abs() < 0.01);
+		assert!((PanLaw::MinusFourPointFiveDb.gains(0.5).0 - db(-4.5)).abs() < 0.01);
+		assert!((PanLaw::Linear.gains(0.5).0 - db(-6.0)).abs() < 0.01);
+	}
+
+	#[test]
+	fn clamps_panning() {
+		assert_gains(PanLaw::ConstantPower, -1.0, (1.0, 0.0));
+		assert_gains(PanLaw::ConstantPower, 2.0, (0.0, 1.0));
+	}
+
+	#[test]
+	fn defaults_to_constant_power() {
+		assert_eq!(PanLaw::default(), PanLaw::ConstantPower);
+	}
+}
@@ -16,6 +16,11 @@ pub enum Easing {
 	PowI(i32),
 	/// Raises `t` to a float power.
 	PowF(f64),
+	/// Follows a quarter of a sine wave.
+	///
+	/// Fading a volume in with [`EaseDirection::Out`] and out
+	/// with [`EaseDirection::In`] gives an equal-power crossfade.
+	Sine,
 }
 
 impl Easing {
@@ -25,6 +30,7 @@ impl Easing {
 			Easing::Linear => t,
 			Easing::PowI(power) => t.powi(*power),
 			Easing::PowF(power) => t.powf(*power),
+			Easing::Sine => 1.0 - (t * std::f64::consts::FRAC_PI_2).cos(),
 		}
 	}
 }
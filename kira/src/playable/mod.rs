@@ -7,8 +7,10 @@ pub(crate) use playables::Playables;
 use crate::{
 	arrangement::{handle::ArrangementHandle, Arrangement, ArrangementId},
 	group::{groups::Groups, GroupId},
+	instance::InstanceLimitBehavior,
 	mixer::TrackIndex,
 	sound::{handle::SoundHandle, Sound, SoundId},
+	Marker,
 };
 
 /// An unique identifier for something you can play multiple instances of.
@@ -76,6 +78,13 @@ impl<'a> Playable<'a> {
 		}
 	}
 
+	pub fn markers(&self) -> &'a [Marker] {
+		match self {
+			Playable::Sound(sound) => sound.markers(),
+			Playable::Arrangement(arrangement) => arrangement.markers(),
+		}
+	}
+
 	pub fn is_in_group(&self, id: GroupId, all_groups: &Groups) -> bool {
 		match self {
 			Playable::Sound(sound) => sound.is_in_group(id, all_groups),
@@ -91,6 +100,20 @@ pub(crate) enum PlayableMut<'a> {
 }
 
 impl<'a> PlayableMut<'a> {
+	pub fn max_instances(&self) -> Option<usize> {
+		match self {
+			PlayableMut::Sound(sound) => sound.max_instances(),
+			PlayableMut::Arrangement(arrangement) => arrangement.max_instances(),
+		}
+	}
+
+	pub fn instance_limit_behavior(&self) -> InstanceLimitBehavior {
+		match self {
+			PlayableMut::Sound(sound) => sound.instance_limit_behavior(),
+			PlayableMut::Arrangement(arrangement) => arrangement.instance_limit_behavior(),
+		}
+	}
+
 	pub fn cooling_down(&self) -> bool {
 		match self {
 			PlayableMut::Sound(sound) => sound.cooling_down(),
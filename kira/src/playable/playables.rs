@@ -72,6 +72,13 @@ impl Playables {
 					unloader.try_send(Resource::Sound(sound)).ok();
 				}
 			}
+			#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+			ResourceCommand::ReloadSound(mut sound) => {
+				if let Some(existing_sound) = self.sounds.get_mut(&sound.id()) {
+					existing_sound.swap_audio(&mut sound);
+				}
+				unloader.try_send(Resource::Sound(sound)).ok();
+			}
 			ResourceCommand::RemoveSound(id) => {
 				if let Some(sound) = self.sounds.remove(&id) {
 					unloader.try_send(Resource::Sound(sound)).ok();
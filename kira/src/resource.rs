@@ -2,10 +2,12 @@ use crate::{
 	arrangement::Arrangement,
 	audio_stream::AudioStream,
 	group::Group,
+	instance::Instance,
 	metronome::Metronome,
 	mixer::{effect_slot::EffectSlot, Track},
 	sequence::SequenceInstance,
 	sound::Sound,
+	spatial::{Emitter, HrirSet},
 };
 
 pub(crate) enum Resource {
@@ -16,5 +18,8 @@ pub(crate) enum Resource {
 	EffectSlot(EffectSlot),
 	Group(Group),
 	Stream(Box<dyn AudioStream>),
+	Instance(Box<Instance>),
 	Metronome(Metronome),
+	HrirSet(HrirSet),
+	Emitter(Box<Emitter>),
 }
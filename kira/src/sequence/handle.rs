@@ -24,6 +24,13 @@ pub enum SequenceInstanceHandleError {
 }
 
 /// Allows you to control an instance of a sequence..
+///
+/// Instances played by a sequence don't have
+/// [`InstanceHandle`](crate::instance::handle::InstanceHandle)s,
+/// and they don't report passing markers or emit
+/// [`InstanceEvent`](crate::instance::InstanceEvent)s. To react
+/// to a point in a sequence, add a custom event to the sequence
+/// with [`Sequence::emit`](crate::sequence::Sequence::emit) instead.
 #[derive(Debug, Clone)]
 pub struct SequenceInstanceHandle<CustomEvent> {
 	id: SequenceInstanceId,
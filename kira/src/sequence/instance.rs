@@ -7,12 +7,13 @@ use uuid::Uuid;
 
 use crate::{
 	group::{groups::Groups, GroupId},
+	instance::InstanceSettings,
 	metronome::{MetronomeId, Metronomes},
 	util::generate_uuid,
 	Tempo,
 };
 
-use super::{RawSequence, SequenceInstanceHandle, SequenceOutputCommand, SequenceStep};
+use super::{RawSequence, SequenceInstanceHandle, SequenceOutput, SequenceStep};
 
 /// A unique identifier for an instance of a [`Sequence`](crate::sequence::Sequence).
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -107,6 +108,17 @@ impl SequenceInstance {
 		}
 	}
 
+	/// Gets the settings for the instance played by the step
+	/// at the given index.
+	pub(crate) fn instance_settings(&self, step: usize) -> Option<&InstanceSettings> {
+		match self.sequence.steps.get(step)? {
+			SequenceStep::PlaySound(_, settings) | SequenceStep::PlayRandom(_, settings) => {
+				Some(settings)
+			}
+			_ => None,
+		}
+	}
+
 	pub(crate) fn start(&mut self) {
 		self.start_step(0);
 	}
@@ -135,7 +147,7 @@ impl SequenceInstance {
 		&mut self,
 		dt: f64,
 		metronomes: &Metronomes,
-		output_command_queue: &mut Vec<SequenceOutputCommand>,
+		output_queue: &mut Vec<SequenceOutput>,
 	) {
 		let metronome = self.metronome.map(|id| metronomes.get(id)).flatten();
 		loop {
@@ -171,16 +183,27 @@ impl SequenceInstance {
 							}
 							SequenceStep::RunCommand(command) => {
 								if !self.muted {
-									output_command_queue.push(*command);
+									output_queue.push(SequenceOutput::RunCommand(*command));
+								}
+								self.start_step(self.position + 1);
+							}
+							SequenceStep::PlaySound(playable, settings) => {
+								if !self.muted {
+									output_queue.push(SequenceOutput::PlaySound(
+										*playable,
+										self.position,
+										settings.id,
+									));
 								}
 								self.start_step(self.position + 1);
 							}
 							SequenceStep::PlayRandom(choices, settings) => {
 								let choice_index = tls_rng().generate_range(0, choices.len());
 								if !self.muted {
-									output_command_queue.push(SequenceOutputCommand::PlaySound(
+									output_queue.push(SequenceOutput::PlaySound(
 										choices[choice_index],
-										*settings,
+										self.position,
+										settings.id,
 									));
 								}
 								self.start_step(self.position + 1);
@@ -248,10 +248,9 @@ impl Default for SequenceInstanceSettings {
 	derive(serde::Serialize, serde::Deserialize)
 )]
 pub(crate) enum SequenceOutputCommand {
-	PlaySound(PlayableId, InstanceSettings),
-	SetInstanceVolume(InstanceId, Value<f64>),
-	SetInstancePitch(InstanceId, Value<f64>),
-	SetInstancePanning(InstanceId, Value<f64>),
+	SetInstanceVolume(InstanceId, Value<f64>, Option<Tween>),
+	SetInstancePitch(InstanceId, Value<f64>, Option<Tween>),
+	SetInstancePanning(InstanceId, Value<f64>, Option<Tween>),
 	PauseInstance(InstanceId, PauseInstanceSettings),
 	ResumeInstance(InstanceId, ResumeInstanceSettings),
 	StopInstance(InstanceId, StopInstanceSettings),
@@ -284,10 +283,20 @@ pub(crate) enum SequenceStep<CustomEvent: Clone + Eq + Hash> {
 	Wait(Duration),
 	WaitForInterval(f64),
 	RunCommand(SequenceOutputCommand),
-	PlayRandom(Vec<PlayableId>, InstanceSettings),
+	PlaySound(PlayableId, Box<InstanceSettings>),
+	PlayRandom(Vec<PlayableId>, Box<InstanceSettings>),
 	EmitCustomEvent(CustomEvent),
 }
 
+/// Something a sequence instance needs the backend to do.
+pub(crate) enum SequenceOutput {
+	RunCommand(SequenceOutputCommand),
+	/// Plays a sound or arrangement using the instance settings
+	/// from the step at the given index, with the instance ID
+	/// the step had when it ran.
+	PlaySound(PlayableId, usize, InstanceId),
+}
+
 impl<CustomEvent: Clone + Eq + Hash> From<SequenceOutputCommand> for SequenceStep<CustomEvent> {
 	fn from(command: SequenceOutputCommand) -> Self {
 		Self::RunCommand(command)
@@ -392,13 +401,16 @@ impl<CustomEvent: Clone + Eq + Hash> Sequence<CustomEvent> {
 	}
 
 	/// Adds a step to play a sound or arrangement.
+	///
+	/// The instance doesn't report passing markers or emit
+	/// [`InstanceEvent`](crate::instance::InstanceEvent)s.
 	pub fn play<P: Into<PlayableId>>(
 		&mut self,
 		playable: P,
 		settings: InstanceSettings,
 	) -> InstanceId {
 		self.steps
-			.push(SequenceOutputCommand::PlaySound(playable.into(), settings).into());
+			.push(SequenceStep::PlaySound(playable.into(), Box::new(settings)));
 		settings.id
 	}
 
@@ -410,26 +422,48 @@ impl<CustomEvent: Clone + Eq + Hash> Sequence<CustomEvent> {
 		settings: InstanceSettings,
 	) -> InstanceId {
 		self.steps
-			.push(SequenceStep::PlayRandom(choices, settings).into());
+			.push(SequenceStep::PlayRandom(choices, Box::new(settings)));
 		settings.id
 	}
 
-	/// Adds a step to set the volume of an instance.
-	pub fn set_instance_volume(&mut self, id: impl Into<InstanceId>, volume: Value<f64>) {
-		self.steps
-			.push(SequenceOutputCommand::SetInstanceVolume(id.into(), volume).into());
+	/// Adds a step to set the volume of an instance, optionally
+	/// tweening it to the new value.
+	pub fn set_instance_volume(
+		&mut self,
+		id: impl Into<InstanceId>,
+		volume: impl Into<Value<f64>>,
+		tween: impl Into<Option<Tween>>,
+	) {
+		self.steps.push(
+			SequenceOutputCommand::SetInstanceVolume(id.into(), volume.into(), tween.into()).into(),
+		);
 	}
 
-	/// Adds a step to set the pitch of an instance.
-	pub fn set_instance_pitch(&mut self, id: impl Into<InstanceId>, pitch: Value<f64>) {
-		self.steps
-			.push(SequenceOutputCommand::SetInstancePitch(id.into(), pitch).into());
+	/// Adds a step to set the pitch of an instance, optionally
+	/// tweening it to the new value.
+	pub fn set_instance_pitch(
+		&mut self,
+		id: impl Into<InstanceId>,
+		pitch: impl Into<Value<f64>>,
+		tween: impl Into<Option<Tween>>,
+	) {
+		self.steps.push(
+			SequenceOutputCommand::SetInstancePitch(id.into(), pitch.into(), tween.into()).into(),
+		);
 	}
 
-	/// Adds a step to set the panning of an instance.
-	pub fn set_instance_panning(&mut self, id: impl Into<InstanceId>, panning: Value<f64>) {
-		self.steps
-			.push(SequenceOutputCommand::SetInstancePanning(id.into(), panning).into());
+	/// Adds a step to set the panning of an instance, optionally
+	/// tweening it to the new value.
+	pub fn set_instance_panning(
+		&mut self,
+		id: impl Into<InstanceId>,
+		panning: impl Into<Value<f64>>,
+		tween: impl Into<Option<Tween>>,
+	) {
+		self.steps.push(
+			SequenceOutputCommand::SetInstancePanning(id.into(), panning.into(), tween.into())
+				.into(),
+		);
 	}
 
 	/// Adds a step to pause an instance.
@@ -621,8 +655,11 @@ impl<CustomEvent: Clone + Eq + Hash> Sequence<CustomEvent> {
 				SequenceStep::Wait(duration) => SequenceStep::Wait(*duration),
 				SequenceStep::WaitForInterval(interval) => SequenceStep::WaitForInterval(*interval),
 				SequenceStep::RunCommand(command) => SequenceStep::RunCommand(*command),
+				SequenceStep::PlaySound(playable, settings) => {
+					SequenceStep::PlaySound(*playable, settings.clone())
+				}
 				SequenceStep::PlayRandom(choices, settings) => {
-					SequenceStep::PlayRandom(choices.clone(), *settings)
+					SequenceStep::PlayRandom(choices.clone(), settings.clone())
 				}
 				SequenceStep::EmitCustomEvent(event) => {
 					SequenceStep::EmitCustomEvent(events.get_index_of(event).unwrap())
@@ -676,22 +713,17 @@ impl RawSequence {
 		for step in steps {
 			match step {
 				SequenceStep::RunCommand(command) => match command {
-					SequenceOutputCommand::PlaySound(_, settings) => {
-						if settings.id == old_id {
-							settings.id = new_id;
-						}
-					}
-					SequenceOutputCommand::SetInstanceVolume(id, _) => {
+					SequenceOutputCommand::SetInstanceVolume(id, _, _) => {
 						if *id == old_id {
 							*id = new_id;
 						}
 					}
-					SequenceOutputCommand::SetInstancePitch(id, _) => {
+					SequenceOutputCommand::SetInstancePitch(id, _, _) => {
 						if *id == old_id {
 							*id = new_id;
 						}
 					}
-					SequenceOutputCommand::SetInstancePanning(id, _) => {
+					SequenceOutputCommand::SetInstancePanning(id, _, _) => {
 						if *id == old_id {
 							*id = new_id;
 						}
@@ -713,7 +745,7 @@ impl RawSequence {
 					}
 					_ => {}
 				},
-				SequenceStep::PlayRandom(_, settings) => {
+				SequenceStep::PlaySound(_, settings) | SequenceStep::PlayRandom(_, settings) => {
 					if settings.id == old_id {
 						settings.id = new_id;
 					}
@@ -731,14 +763,7 @@ impl RawSequence {
 	fn update_instance_ids(&mut self) {
 		for i in 0..self.steps.len() {
 			match &self.steps[i] {
-				SequenceStep::RunCommand(command) => match command {
-					SequenceOutputCommand::PlaySound(_, settings) => {
-						let old_id = settings.id;
-						Self::convert_ids(&mut self.steps, old_id, InstanceId::new());
-					}
-					_ => {}
-				},
-				SequenceStep::PlayRandom(_, settings) => {
+				SequenceStep::PlaySound(_, settings) | SequenceStep::PlayRandom(_, settings) => {
 					let old_id = settings.id;
 					Self::convert_ids(&mut self.steps, old_id, InstanceId::new());
 				}
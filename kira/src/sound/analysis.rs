@@ -0,0 +1,275 @@
+use std::f64::consts::PI;
+
+use crate::Frame;
+
+/// A target level that a sound's volume can be adjusted
+/// to when it's loaded.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum Normalization {
+	/// Adjusts the volume so the loudest sample has the
+	/// given amplitude (where 1.0 is full scale).
+	Peak(f64),
+	/// Adjusts the volume so the sound has the given
+	/// integrated loudness (in LUFS).
+	Loudness(f64),
+}
+
+impl Normalization {
+	/// Gets the volume frames should be multiplied by to reach
+	/// the target level, or `None` if the frames are silent.
+	pub(crate) fn gain(self, frames: &[Frame], sample_rate: u32) -> Option<f64> {
+		match self {
+			Normalization::Peak(target) => {
+				let peak = peak(frames.iter().copied());
+				if peak > 0.0 {
+					Some(target / peak)
+				} else {
+					None
+				}
+			}
+			Normalization::Loudness(target) => {
+				let loudness = loudness(frames.iter().copied(), sample_rate);
+				if loudness.is_finite() {
+					Some(10.0f64.powf((target - loudness) / 20.0))
+				} else {
+					None
+				}
+			}
+		}
+	}
+}
+
+/// Gets the highest absolute sample value of any channel.
+pub(crate) fn peak(frames: impl Iterator<Item = Frame>) -> f64 {
+	frames.fold(0.0, |peak, frame| {
+		peak.max(frame.left.abs() as f64)
+			.max(frame.right.abs() as f64)
+	})
+}
+
+/// Gets the root mean square of the samples of both channels.
+pub(crate) fn rms(frames: impl Iterator<Item = Frame>) -> f64 {
+	let mut sum = 0.0;
+	let mut num_samples = 0;
+	for frame in frames {
+		sum += (frame.left as f64).powi(2) + (frame.right as f64).powi(2);
+		num_samples += 2;
+	}
+	if num_samples == 0 {
+		return 0.0;
+	}
+	(sum / num_samples as f64).sqrt()
+}
+
+/// A second-order IIR filter.
+struct Biquad {
+	b: [f64; 3],
+	a: [f64; 3],
+	x: [f64; 2],
+	y: [f64; 2],
+}
+
+impl Biquad {
+	fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+		Self {
+			b,
+			a,
+			x: [0.0; 2],
+			y: [0.0; 2],
+		}
+	}
+
+	fn process(&mut self, input: f64) -> f64 {
+		let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+			- self.a[1] * self.y[0]
+			- self.a[2] * self.y[1];
+		self.x = [input, self.x[0]];
+		self.y = [output, self.y[0]];
+		output
+	}
+}
+
+/// The K-weighting filter from ITU-R BS.1770, which
+/// approximates how loud humans perceive different
+/// frequencies to be.
+struct KWeightingFilter {
+	shelf: Biquad,
+	high_pass: Biquad,
+}
+
+impl KWeightingFilter {
+	fn new(sample_rate: u32) -> Self {
+		let sample_rate = sample_rate as f64;
+		// high shelf filter modeling the acoustic effect of the head
+		let frequency = 1681.974450955533;
+		let gain = 3.999843853973347;
+		let q = 0.7071752369554196;
+		let k = (PI * frequency / sample_rate).tan();
+		let vh = 10.0f64.powf(gain / 20.0);
+		let vb = vh.powf(0.4996667741545416);
+		let a0 = 1.0 + k / q + k * k;
+		let shelf = Biquad::new(
+			[
+				(vh + vb * k / q + k * k) / a0,
+				2.0 * (k * k - vh) / a0,
+				(vh - vb * k / q + k * k) / a0,
+			],
+			[1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+		);
+		// high pass filter (the "RLB" weighting curve)
+		let frequency = 38.13547087602444;
+		let q = 0.5003270373238773;
+		let k = (PI * frequency / sample_rate).tan();
+		let a0 = 1.0 + k / q + k * k;
+		let high_pass = Biquad::new(
+			[1.0, -2.0, 1.0],
+			[1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+		);
+		Self { shelf, high_pass }
+	}
+
+	fn process(&mut self, input: f64) -> f64 {
+		self.high_pass.process(self.shelf.process(input))
+	}
+}
+
+fn mean_square_to_loudness(mean_square: f64) -> f64 {
+	-0.691 + 10.0 * mean_square.log10()
+}
+
+/// Gets the integrated loudness (in LUFS) as defined
+/// by ITU-R BS.1770 and EBU R128.
+///
+/// Returns negative infinity if the audio is silent or
+/// shorter than a single 400ms measurement block.
+pub(crate) fn loudness(frames: impl Iterator<Item = Frame>, sample_rate: u32) -> f64 {
+	const ABSOLUTE_GATE: f64 = -70.0;
+	const RELATIVE_GATE: f64 = -10.0;
+
+	// measure the power of each 100ms step, then combine
+	// them into overlapping 400ms blocks
+	let step_length = (sample_rate as usize / 10).max(1);
+	let mut left_filter = KWeightingFilter::new(sample_rate);
+	let mut right_filter = KWeightingFilter::new(sample_rate);
+	let mut step_powers = vec![];
+	let mut current_step_power = 0.0;
+	let mut current_step_length = 0;
+	for frame in frames {
+		let left = left_filter.process(frame.left as f64);
+		let right = right_filter.process(frame.right as f64);
+		current_step_power += left * left + right * right;
+		current_step_length += 1;
+		if current_step_length == step_length {
+			step_powers.push(current_step_power);
+			current_step_power = 0.0;
+			current_step_length = 0;
+		}
+	}
+	let block_mean_squares: Vec<f64> = step_powers
+		.windows(4)
+		.map(|steps| steps.iter().sum::<f64>() / (step_length * 4) as f64)
+		.collect();
+
+	let gated_mean = |threshold: f64| {
+		let mut sum = 0.0;
+		let mut num_blocks = 0;
+		for mean_square in &block_mean_squares {
+			if mean_square_to_loudness(*mean_square) > threshold {
+				sum += mean_square;
+				num_blocks += 1;
+			}
+		}
+		if num_blocks == 0 {
+			None
+		} else {
+			Some(sum / num_blocks as f64)
+		}
+	};
+	let relative_threshold = match gated_mean(ABSOLUTE_GATE) {
+		Some(mean_square) => mean_square_to_loudness(mean_square) + RELATIVE_GATE,
+		None => return f64::NEG_INFINITY,
+	};
+	match gated_mean(relative_threshold.max(ABSOLUTE_GATE)) {
+		Some(mean_square) => mean_square_to_loudness(mean_square),
+		None => f64::NEG_INFINITY,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SAMPLE_RATE: u32 = 48000;
+
+	/// Generates a 1 kHz sine wave with the given peak level (in dBFS).
+	fn sine(level: f64, duration: f64, left: bool, right: bool) -> Vec<Frame> {
+		let amplitude = 10.0f64.powf(level / 20.0);
+		(0..(duration * SAMPLE_RATE as f64) as usize)
+			.map(|i| {
+				let time = i as f64 / SAMPLE_RATE as f64;
+				let sample = (amplitude * (2.0 * PI * 1000.0 * time).sin()) as f32;
+				Frame::new(
+					if left { sample } else { 0.0 },
+					if right { sample } else { 0.0 },
+				)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn stereo_sine_loudness() {
+		// EBU Tech 3341 test case 1, at -20 dBFS instead of -23
+		let frames = sine(-20.0, 20.0, true, true);
+		let loudness = loudness(frames.into_iter(), SAMPLE_RATE);
+		assert!((loudness - -20.0).abs() < 0.1, "{}", loudness);
+	}
+
+	#[test]
+	fn single_channel_sine_loudness() {
+		// each channel contributes half of the power
+		let frames = sine(-20.0, 20.0, true, false);
+		let loudness = loudness(frames.into_iter(), SAMPLE_RATE);
+		assert!((loudness - -23.01).abs() < 0.1, "{}", loudness);
+	}
+
+	#[test]
+	fn relative_gate_ignores_quiet_sections() {
+		// EBU Tech 3341 test case 3, with shorter sections
+		let mut frames = sine(-36.0, 5.0, true, true);
+		frames.extend(sine(-23.0, 20.0, true, true));
+		frames.extend(sine(-36.0, 5.0, true, true));
+		let loudness = loudness(frames.into_iter(), SAMPLE_RATE);
+		assert!((loudness - -23.0).abs() < 0.1, "{}", loudness);
+	}
+
+	#[test]
+	fn silence_has_no_loudness() {
+		let frames = vec![Frame::from_mono(0.0); SAMPLE_RATE as usize * 2];
+		assert_eq!(
+			loudness(frames.iter().copied(), SAMPLE_RATE),
+			f64::NEG_INFINITY
+		);
+		assert_eq!(
+			Normalization::Loudness(-23.0).gain(&frames, SAMPLE_RATE),
+			None
+		);
+		assert_eq!(Normalization::Peak(1.0).gain(&frames, SAMPLE_RATE), None);
+	}
+
+	#[test]
+	fn short_audio_has_no_loudness() {
+		let frames = sine(-20.0, 0.3, true, true);
+		assert_eq!(loudness(frames.into_iter(), SAMPLE_RATE), f64::NEG_INFINITY);
+	}
+
+	#[test]
+	fn peak_and_rms() {
+		let frames = sine(-20.0, 1.0, true, true);
+		assert!((peak(frames.iter().copied()) - 0.1).abs() < 1e-4);
+		assert!((rms(frames.iter().copied()) - 0.1 / 2.0f64.sqrt()).abs() < 1e-4);
+	}
+}
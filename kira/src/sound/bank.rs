@@ -0,0 +1,232 @@
+//! Loads collections of sounds described by serializable manifests.
+//!
+//! A [`SoundBankManifest`] lists sounds by name along with the file
+//! to load each one from and its settings. Since track and group IDs
+//! are generated at runtime, sounds in a manifest refer to tracks and
+//! groups by name, and those names are mapped to actual tracks and
+//! groups using [`SoundBankNames`].
+//!
+//! ```no_run
+//! # use kira::{
+//! # 	manager::{AudioManager, AudioManagerSettings},
+//! # 	mixer::TrackSettings,
+//! # 	sound::bank::{SoundBankManifest, SoundBankNames},
+//! # 	instance::InstanceSettings,
+//! # };
+//! # let mut audio_manager = AudioManager::new(AudioManagerSettings::default())?;
+//! # let manifest = SoundBankManifest::default();
+//! let sfx_track = audio_manager.add_sub_track(TrackSettings::new())?;
+//! let names = SoundBankNames::new().track("sfx", &sfx_track);
+//! // `manifest` can be deserialized from any format serde supports
+//! let mut bank = audio_manager.load_sound_bank(&manifest, "assets/sounds", &names)?;
+//! if let Some(sound) = bank.get_mut("explosion") {
+//! 	sound.play(InstanceSettings::default())?;
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{collections::HashMap, path::PathBuf};
+
+use indexmap::IndexMap;
+
+use crate::{group::GroupId, mixer::TrackIndex};
+
+use super::{handle::SoundHandle, SoundSettings};
+
+/// A description of a sound to load as part of a sound bank.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SoundBankEntry {
+	/// The name used to look up the sound in the bank.
+	pub name: String,
+	/// The path of the audio file to load, relative to
+	/// the bank's directory.
+	pub path: PathBuf,
+	/// The name of the track instances of the sound
+	/// should play on by default.
+	///
+	/// If set, this replaces the default track in the
+	/// sound's settings.
+	#[serde(default)]
+	pub track: Option<String>,
+	/// The names of the groups the sound belongs to.
+	///
+	/// If not empty, this replaces the groups in the
+	/// sound's settings.
+	#[serde(default)]
+	pub groups: Vec<String>,
+	/// The settings for the sound.
+	#[serde(default)]
+	pub settings: SoundSettings,
+}
+
+/// A list of sounds to load together.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SoundBankManifest {
+	/// The sounds in the bank.
+	pub sounds: Vec<SoundBankEntry>,
+}
+
+/// Maps the track and group names used in sound bank
+/// manifests to actual tracks and groups.
+#[derive(Debug, Clone, Default)]
+pub struct SoundBankNames {
+	pub(crate) tracks: HashMap<String, TrackIndex>,
+	pub(crate) groups: HashMap<String, GroupId>,
+}
+
+impl SoundBankNames {
+	/// Creates a new `SoundBankNames` with no names.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Gives a track a name that sound bank entries can use.
+	pub fn track(mut self, name: impl Into<String>, track: impl Into<TrackIndex>) -> Self {
+		self.tracks.insert(name.into(), track.into());
+		self
+	}
+
+	/// Gives a group a name that sound bank entries can use.
+	pub fn group(mut self, name: impl Into<String>, group: impl Into<GroupId>) -> Self {
+		self.groups.insert(name.into(), group.into());
+		self
+	}
+}
+
+/// A collection of loaded sounds that can be looked up by name.
+#[derive(Debug, Clone, Default)]
+pub struct SoundBank {
+	pub(crate) sounds: IndexMap<String, SoundHandle>,
+}
+
+impl SoundBank {
+	/// Gets the sound with the given name.
+	pub fn get(&self, name: &str) -> Option<&SoundHandle> {
+		self.sounds.get(name)
+	}
+
+	/// Gets a mutable reference to the sound with the given name.
+	pub fn get_mut(&mut self, name: &str) -> Option<&mut SoundHandle> {
+		self.sounds.get_mut(name)
+	}
+
+	/// Returns an iterator over the names and handles of
+	/// the sounds in the bank.
+	pub fn iter(&self) -> indexmap::map::Iter<'_, String, SoundHandle> {
+		self.sounds.iter()
+	}
+
+	/// Gets the number of sounds in the bank.
+	pub fn len(&self) -> usize {
+		self.sounds.len()
+	}
+
+	/// Returns `true` if the bank has no sounds.
+	pub fn is_empty(&self) -> bool {
+		self.sounds.is_empty()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_manifest() {
+		let manifest: SoundBankManifest = serde_json::from_str(
+			r#"{
+				"sounds": [
+					{ "name": "explosion", "path": "explosion.ogg" },
+					{
+						"name": "footstep",
+						"path": "player/footstep.wav",
+						"track": "sfx",
+						"groups": ["player", "gameplay"],
+						"settings": { "cooldown": 0.25 }
+					}
+				]
+			}"#,
+		)
+		.unwrap();
+		assert_eq!(manifest.sounds.len(), 2);
+		let explosion = &manifest.sounds[0];
+		assert_eq!(explosion.name, "explosion");
+		assert_eq!(explosion.path, PathBuf::from("explosion.ogg"));
+		assert_eq!(explosion.track, None);
+		assert!(explosion.groups.is_empty());
+		let footstep = &manifest.sounds[1];
+		assert_eq!(footstep.path, PathBuf::from("player/footstep.wav"));
+		assert_eq!(footstep.track.as_deref(), Some("sfx"));
+		assert_eq!(footstep.groups, ["player", "gameplay"]);
+		assert_eq!(footstep.settings.cooldown, Some(0.25));
+	}
+
+	#[test]
+	fn parses_empty_manifest() {
+		let manifest: SoundBankManifest = serde_json::from_str("{}").unwrap();
+		assert!(manifest.sounds.is_empty());
+	}
+
+	#[test]
+	fn rejects_entries_without_a_path() {
+		assert!(
+			serde_json::from_str::<SoundBankManifest>(r#"{ "sounds": [{ "name": "a" }] }"#)
+				.is_err()
+		);
+	}
+
+	#[cfg(all(feature = "benchmarking", feature = "wav"))]
+	#[test]
+	fn removes_loaded_sounds_if_a_sound_fails_to_load() {
+		use crate::manager::{error::LoadSoundBankError, AudioManager, AudioManagerSettings};
+
+		let directory = std::env::temp_dir().join(format!("kira-bank-test-{}", std::process::id()));
+		std::fs::create_dir_all(&directory).unwrap();
+		let mut writer = hound::WavWriter::create(
+			directory.join("beep.wav"),
+			hound::WavSpec {
+				channels: 1,
+				sample_rate: 48000,
+				bits_per_sample: 16,
+				sample_format: hound::SampleFormat::Int,
+			},
+		)
+		.unwrap();
+		for _ in 0..480 {
+			writer.write_sample(0i16).unwrap();
+		}
+		writer.finalize().unwrap();
+
+		let (mut audio_manager, _backend) =
+			AudioManager::new_without_audio_thread(AudioManagerSettings {
+				num_sounds: 1,
+				..Default::default()
+			});
+		let entry = |name: &str, path: &str| SoundBankEntry {
+			name: name.into(),
+			path: path.into(),
+			track: None,
+			groups: vec![],
+			settings: SoundSettings::default(),
+		};
+		let manifest = SoundBankManifest {
+			sounds: vec![entry("beep", "beep.wav"), entry("missing", "missing.wav")],
+		};
+		let result = audio_manager.load_sound_bank(&manifest, &directory, &SoundBankNames::new());
+		assert!(matches!(
+			result,
+			Err(LoadSoundBankError::LoadSoundError { sound, .. }) if sound == "missing"
+		));
+		// the sound limit is 1, so this only succeeds if
+		// "beep" was removed when "missing" failed to load
+		let manifest = SoundBankManifest {
+			sounds: vec![entry("beep", "beep.wav")],
+		};
+		let bank = audio_manager
+			.load_sound_bank(&manifest, &directory, &SoundBankNames::new())
+			.unwrap();
+		assert_eq!(bank.len(), 1);
+		std::fs::remove_dir_all(&directory).ok();
+	}
+}
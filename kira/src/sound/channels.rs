@@ -0,0 +1,195 @@
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+use std::f32::consts::FRAC_1_SQRT_2;
+
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+use crate::Frame;
+
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+use super::error::SoundFromFileError;
+
+/// How a sound with any number of channels should be converted
+/// to stereo audio when it's loaded from a file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum ChannelPolicy {
+	/// Mixes every channel down to stereo using the standard
+	/// (ITU-R BS.775) downmix coefficients for the file's
+	/// speaker layout.
+	///
+	/// Mono and stereo files are loaded as-is. Files with more
+	/// channels are downmixed if their format defines a speaker
+	/// layout for that number of channels (for example, quad, 5.1,
+	/// or 7.1). The LFE channel is discarded.
+	Downmix,
+	/// Treats the first four channels of the file as first-order
+	/// ambisonics (AmbiX: ACN channel ordering with SN3D
+	/// normalization) and decodes them to stereo using
+	/// a pair of virtual cardioid microphones pointing
+	/// left and right.
+	AmbisonicDownmix,
+	/// Uses the channel with the given index for both the
+	/// left and right channels.
+	Mono(usize),
+	/// Uses the channels with the given indices for the
+	/// left and right channels respectively.
+	Stereo(usize, usize),
+}
+
+impl Default for ChannelPolicy {
+	fn default() -> Self {
+		Self::Downmix
+	}
+}
+
+/// The order speakers are assigned to channels in a file format.
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum ChannelOrder {
+	/// The order used by wav, flac, and mp3 files.
+	Wav,
+	/// The order used by ogg vorbis files.
+	Vorbis,
+}
+
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Speaker {
+	FrontLeft,
+	FrontRight,
+	FrontCenter,
+	LowFrequency,
+	BackLeft,
+	BackRight,
+	BackCenter,
+	SideLeft,
+	SideRight,
+}
+
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+impl Speaker {
+	/// Gets how much of this speaker's signal goes to the left
+	/// and right channels when downmixing to stereo.
+	fn downmix_gains(self) -> (f32, f32) {
+		match self {
+			Speaker::FrontLeft => (1.0, 0.0),
+			Speaker::FrontRight => (0.0, 1.0),
+			Speaker::FrontCenter => (FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+			Speaker::LowFrequency => (0.0, 0.0),
+			Speaker::BackLeft | Speaker::SideLeft => (FRAC_1_SQRT_2, 0.0),
+			Speaker::BackRight | Speaker::SideRight => (0.0, FRAC_1_SQRT_2),
+			Speaker::BackCenter => (0.5, 0.5),
+		}
+	}
+}
+
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+impl ChannelOrder {
+	fn speakers(self, num_channels: usize) -> Option<&'static [Speaker]> {
+		use Speaker::*;
+		match (self, num_channels) {
+			(_, 4) => Some(&[FrontLeft, FrontRight, BackLeft, BackRight]),
+			(ChannelOrder::Wav, 3) => Some(&[FrontLeft, FrontRight, FrontCenter]),
+			(ChannelOrder::Wav, 5) => {
+				Some(&[FrontLeft, FrontRight, FrontCenter, BackLeft, BackRight])
+			}
+			(ChannelOrder::Wav, 6) => Some(&[
+				FrontLeft,
+				FrontRight,
+				FrontCenter,
+				LowFrequency,
+				BackLeft,
+				BackRight,
+			]),
+			(ChannelOrder::Wav, 8) => Some(&[
+				FrontLeft,
+				FrontRight,
+				FrontCenter,
+				LowFrequency,
+				BackLeft,
+				BackRight,
+				SideLeft,
+				SideRight,
+			]),
+			(ChannelOrder::Vorbis, 3) => Some(&[FrontLeft, FrontCenter, FrontRight]),
+			(ChannelOrder::Vorbis, 5) => {
+				Some(&[FrontLeft, FrontCenter, FrontRight, BackLeft, BackRight])
+			}
+			(ChannelOrder::Vorbis, 6) => Some(&[
+				FrontLeft,
+				FrontCenter,
+				FrontRight,
+				BackLeft,
+				BackRight,
+				LowFrequency,
+			]),
+			(ChannelOrder::Vorbis, 7) => Some(&[
+				FrontLeft,
+				FrontCenter,
+				FrontRight,
+				SideLeft,
+				SideRight,
+				BackCenter,
+				LowFrequency,
+			]),
+			(ChannelOrder::Vorbis, 8) => Some(&[
+				FrontLeft,
+				FrontCenter,
+				FrontRight,
+				SideLeft,
+				SideRight,
+				BackLeft,
+				BackRight,
+				LowFrequency,
+			]),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+impl ChannelPolicy {
+	/// Converts the samples for each channel of a single
+	/// frame of audio to a stereo frame.
+	pub(crate) fn frame(
+		self,
+		samples: &[f32],
+		order: ChannelOrder,
+	) -> Result<Frame, SoundFromFileError> {
+		let get = |index: usize| {
+			samples
+				.get(index)
+				.copied()
+				.ok_or(SoundFromFileError::NoChannelWithIndex(index))
+		};
+		match self {
+			ChannelPolicy::Downmix => match samples.len() {
+				1 => Ok(Frame::from_mono(samples[0])),
+				2 => Ok(Frame::new(samples[0], samples[1])),
+				num_channels => {
+					let speakers = order
+						.speakers(num_channels)
+						.ok_or(SoundFromFileError::UnsupportedChannelConfiguration)?;
+					let mut frame = Frame::from_mono(0.0);
+					for (sample, speaker) in samples.iter().zip(speakers) {
+						let (left_gain, right_gain) = speaker.downmix_gains();
+						frame += Frame::new(sample * left_gain, sample * right_gain);
+					}
+					Ok(frame)
+				}
+			},
+			ChannelPolicy::AmbisonicDownmix => {
+				if samples.len() < 4 {
+					return Err(SoundFromFileError::UnsupportedChannelConfiguration);
+				}
+				let w = samples[0];
+				let y = samples[1];
+				Ok(Frame::new(0.5 * (w + y), 0.5 * (w - y)))
+			}
+			ChannelPolicy::Mono(index) => Ok(Frame::from_mono(get(index)?)),
+			ChannelPolicy::Stereo(left, right) => Ok(Frame::new(get(left)?, get(right)?)),
+		}
+	}
+}
@@ -0,0 +1,224 @@
+use crate::{parameter::tween::Easing, Frame, Marker};
+
+use super::{
+	samples::{SampleSlice, Samples},
+	Sound, SoundId,
+};
+
+/// Functions for creating new sounds by processing
+/// existing ones.
+///
+/// Each function returns a new sound with its own ID that uses
+/// the same settings and sample format as the original sound.
+/// Semantic durations, loop start points, and markers are
+/// moved along with the audio they refer to.
+impl Sound {
+	/// Creates a new sound with a new ID and this sound's
+	/// settings from edited frames.
+	fn with_frames(&self, frames: Vec<Frame>) -> Self {
+		let mut samples = SampleSlice::new(Samples::new(
+			frames,
+			self.samples.format(),
+			self.load_settings.collapse_mono,
+		));
+		if self.samples.has_overview() {
+			samples.build_overview();
+		}
+		Self {
+			id: SoundId::new(),
+			sample_rate: self.sample_rate,
+			duration: samples.len() as f64 / self.sample_rate as f64,
+			samples,
+			default_track: self.default_track,
+			cooldown: self.cooldown,
+			semantic_duration: self.semantic_duration,
+			default_loop_start: self.default_loop_start,
+			groups: self.groups.clone(),
+			markers: self.markers.clone(),
+			max_instances: self.max_instances,
+			instance_limit_behavior: self.instance_limit_behavior,
+			cooldown_timer: 0.0,
+			load_settings: self.load_settings,
+			shared_duration: None,
+		}
+	}
+
+	/// Gets this sound's frames at the given sample rate.
+	fn frames_at_sample_rate(&self, sample_rate: u32) -> Vec<Frame> {
+		if sample_rate == self.sample_rate {
+			return self.samples.to_frames();
+		}
+		let num_frames = (self.duration * sample_rate as f64).round() as usize;
+		(0..num_frames)
+			.map(|i| self.get_frame_at_position(i as f64 / sample_rate as f64))
+			.collect()
+	}
+
+	/// Returns a copy of this sound that plays backwards.
+	///
+	/// Markers are mirrored to the same audio in the reversed
+	/// sound. The semantic duration and default loop start
+	/// point are removed, since they don't have a meaningful
+	/// equivalent in the reversed sound.
+	pub fn reverse(&self) -> Self {
+		let mut frames = self.samples.to_frames();
+		frames.reverse();
+		let mut markers: Vec<Marker> = self
+			.markers
+			.iter()
+			.map(|marker| Marker::new(self.duration - marker.position, marker.name.clone()))
+			.collect();
+		markers.reverse();
+		Self {
+			semantic_duration: None,
+			default_loop_start: None,
+			markers,
+			..self.with_frames(frames)
+		}
+	}
+
+	/// Returns the section of this sound between `start` and
+	/// `end` (in seconds).
+	///
+	/// Unlike [`Sound::slice`], this copies the audio, so the
+	/// original sound's samples can be freed. Markers outside
+	/// of the range are removed, and the semantic duration and
+	/// default loop start point are removed if they fall
+	/// outside of the range.
+	pub fn trim(&self, start: f64, end: f64) -> Self {
+		let slice = self.samples.slice(
+			(start.max(0.0) * self.sample_rate as f64).round() as usize,
+			(end.max(0.0) * self.sample_rate as f64).round() as usize,
+		);
+		let trimmed = self.with_frames(slice.to_frames());
+		let start = start.max(0.0).min(self.duration);
+		let in_range = |position: &f64| *position >= 0.0 && *position <= trimmed.duration;
+		Self {
+			semantic_duration: self
+				.semantic_duration
+				.map(|duration| duration - start)
+				.filter(|duration| *duration > 0.0 && in_range(duration)),
+			default_loop_start: self
+				.default_loop_start
+				.map(|loop_start| loop_start - start)
+				.filter(in_range),
+			markers: self
+				.markers
+				.iter()
+				.map(|marker| Marker::new(marker.position - start, marker.name.clone()))
+				.filter(|marker| in_range(&marker.position))
+				.collect(),
+			..trimmed
+		}
+	}
+
+	/// Returns a copy of this sound whose volume rises from
+	/// silence over the first `duration` seconds, following
+	/// the given easing curve.
+	pub fn fade_in(&self, duration: f64, easing: Easing) -> Self {
+		let mut frames = self.samples.to_frames();
+		let fade_length = (duration * self.sample_rate as f64) as usize;
+		for (i, frame) in frames.iter_mut().take(fade_length).enumerate() {
+			*frame *= easing.apply(i as f64 / fade_length as f64) as f32;
+		}
+		self.with_frames(frames)
+	}
+
+	/// Returns a copy of this sound whose volume falls to
+	/// silence over the last `duration` seconds, following
+	/// the given easing curve.
+	pub fn fade_out(&self, duration: f64, easing: Easing) -> Self {
+		let mut frames = self.samples.to_frames();
+		let fade_length = (duration * self.sample_rate as f64) as usize;
+		for (i, frame) in frames.iter_mut().rev().take(fade_length).enumerate() {
+			*frame *= easing.apply(i as f64 / fade_length as f64) as f32;
+		}
+		self.with_frames(frames)
+	}
+
+	/// Returns a copy of this sound with its volume
+	/// multiplied by the given factor.
+	pub fn gain(&self, volume: f64) -> Self {
+		let mut frames = self.samples.to_frames();
+		for frame in &mut frames {
+			*frame *= volume as f32;
+		}
+		self.with_frames(frames)
+	}
+
+	/// Returns a sound that plays this sound followed
+	/// by each of the other sounds in order.
+	///
+	/// The other sounds are resampled to this sound's sample
+	/// rate if necessary, and their markers are moved along
+	/// with their audio. The semantic duration of the new
+	/// sound ends at the semantic end of the last sound, if
+	/// it has one.
+	pub fn concatenate(&self, others: &[&Sound]) -> Self {
+		let mut frames = self.samples.to_frames();
+		let mut markers = self.markers.clone();
+		let mut semantic_duration = self.semantic_duration;
+		for other in others {
+			let offset = frames.len() as f64 / self.sample_rate as f64;
+			frames.extend(other.frames_at_sample_rate(self.sample_rate));
+			markers.extend(
+				other
+					.markers
+					.iter()
+					.map(|marker| Marker::new(marker.position + offset, marker.name.clone())),
+			);
+			semantic_duration = other.semantic_duration.map(|duration| duration + offset);
+		}
+		Self {
+			semantic_duration,
+			markers,
+			..self.with_frames(frames)
+		}
+	}
+
+	/// Returns a sound that plays this sound and each of the
+	/// other sounds at the same time.
+	///
+	/// The other sounds are resampled to this sound's sample
+	/// rate if necessary. The new sound is as long as the
+	/// longest of the sounds, its semantic duration is the
+	/// longest of their semantic durations, and it has the
+	/// markers of all of the sounds.
+	pub fn mix(&self, others: &[&Sound]) -> Self {
+		let mut frames = self.samples.to_frames();
+		let mut markers = self.markers.clone();
+		let mut semantic_duration = self.semantic_duration;
+		for other in others {
+			let other_frames = other.frames_at_sample_rate(self.sample_rate);
+			if other_frames.len() > frames.len() {
+				frames.resize(other_frames.len(), Frame::from_mono(0.0));
+			}
+			for (frame, other_frame) in frames.iter_mut().zip(other_frames) {
+				*frame += other_frame;
+			}
+			markers.extend(other.markers.iter().cloned());
+			semantic_duration = match (semantic_duration, other.semantic_duration) {
+				(Some(a), Some(b)) => Some(a.max(b)),
+				(a, b) => a.or(b),
+			};
+		}
+		markers.sort_by(|a, b| a.position.total_cmp(&b.position));
+		Self {
+			semantic_duration,
+			markers,
+			..self.with_frames(frames)
+		}
+	}
+
+	/// Returns a copy of this sound with the left and right
+	/// channels averaged together.
+	pub fn to_mono(&self) -> Self {
+		let frames = self
+			.samples
+			.to_frames()
+			.iter()
+			.map(|frame| Frame::from_mono((frame.left + frame.right) * 0.5))
+			.collect();
+		self.with_frames(frames)
+	}
+}
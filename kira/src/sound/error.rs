@@ -6,10 +6,15 @@ use thiserror::Error;
 /// from a file.
 #[derive(Debug, Error)]
 pub enum SoundFromFileError {
-	/// The sound has more than two channels.
-	#[error("Only mono and stereo audio is supported")]
+	/// The sound's channels cannot be converted to stereo
+	/// using the chosen [`ChannelPolicy`](super::ChannelPolicy).
+	#[error("The channel configuration of the sound is not supported by the channel policy")]
 	UnsupportedChannelConfiguration,
 
+	/// The channel policy refers to a channel the sound doesn't have.
+	#[error("The sound does not have a channel with index {0}")]
+	NoChannelWithIndex(usize),
+
 	/// The sound is in an unsupported file format.
 	#[error("Only .mp3, .ogg, .flac, and .wav files are supported")]
 	UnsupportedAudioFileFormat,
@@ -1,5 +1,8 @@
 //! An interface for controlling sounds.
 
+use std::sync::{Arc, Mutex, PoisonError};
+
+use atomic::{Atomic, Ordering};
 use flume::Sender;
 use thiserror::Error;
 
@@ -9,10 +12,14 @@ use crate::{
 		handle::InstanceHandle, Instance, InstanceSettings, PauseInstanceSettings,
 		ResumeInstanceSettings, StopInstanceSettings,
 	},
-	mixer::TrackIndex,
+	mixer::{
+		effect::{Effect, EffectSettings},
+		TrackIndex,
+	},
+	Marker,
 };
 
-use super::{Sound, SoundId};
+use super::{Sound, SoundId, WaveformBucket, WaveformSource};
 
 /// Something that can go wrong when using a [`SoundHandle`] to
 /// control a sound.
@@ -27,10 +34,12 @@ pub enum SoundHandleError {
 #[derive(Debug, Clone)]
 pub struct SoundHandle {
 	id: SoundId,
-	duration: f64,
+	duration: Arc<Atomic<f64>>,
 	default_track: TrackIndex,
 	semantic_duration: Option<f64>,
 	default_loop_start: Option<f64>,
+	markers: Arc<Vec<Marker>>,
+	waveform_source: Arc<Mutex<WaveformSource>>,
 	command_sender: Sender<Command>,
 }
 
@@ -38,10 +47,12 @@ impl SoundHandle {
 	pub(crate) fn new(sound: &Sound, command_sender: Sender<Command>) -> Self {
 		Self {
 			id: sound.id(),
-			duration: sound.duration(),
+			duration: Arc::new(Atomic::new(sound.duration())),
 			default_track: sound.default_track(),
 			semantic_duration: sound.semantic_duration(),
 			default_loop_start: sound.default_loop_start(),
+			markers: Arc::new(sound.markers().to_vec()),
+			waveform_source: Arc::new(Mutex::new(sound.waveform_source())),
 			command_sender,
 		}
 	}
@@ -52,8 +63,14 @@ impl SoundHandle {
 	}
 
 	/// Returns the duration of the sound (in seconds).
+	///
+	/// This is updated if the sound is reloaded.
 	pub fn duration(&self) -> f64 {
-		self.duration
+		self.duration.load(Ordering::Relaxed)
+	}
+
+	pub(crate) fn shared_duration(&self) -> Arc<Atomic<f64>> {
+		self.duration.clone()
 	}
 
 	/// Returns the default track instances of this
@@ -75,18 +92,80 @@ impl SoundHandle {
 		self.default_loop_start
 	}
 
+	/// Summarizes the audio between `start` and `end`
+	/// (in seconds) for drawing a waveform.
+	///
+	/// This works the same way as [`Sound::waveform`], and
+	/// uses the new audio if the sound is reloaded.
+	pub fn waveform(&self, start: f64, end: f64, num_buckets: usize) -> Vec<WaveformBucket> {
+		self.waveform_source().waveform(start, end, num_buckets)
+	}
+
+	pub(crate) fn waveform_source(&self) -> WaveformSource {
+		self.waveform_source
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.clone()
+	}
+
+	/// Sets the audio used for drawing waveforms after
+	/// the sound is reloaded.
+	#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+	pub(crate) fn set_waveform_source(&self, waveform_source: WaveformSource) {
+		*self
+			.waveform_source
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner) = waveform_source;
+	}
+
 	/// Plays the sound.
 	pub fn play(&mut self, settings: InstanceSettings) -> Result<InstanceHandle, SoundHandleError> {
+		self.play_with_effects(settings, vec![])
+	}
+
+	/// Creates an instance of the sound and a handle to
+	/// control it without sending the instance to the
+	/// audio thread.
+	pub(crate) fn create_instance(&self, settings: InstanceSettings) -> (Instance, InstanceHandle) {
+		let (marker_event_sender, marker_event_receiver) =
+			flume::bounded(settings.event_queue_capacity);
+		let (event_sender, event_receiver) = flume::bounded(settings.event_queue_capacity);
+		let duration = self.duration();
 		let id = settings.id;
 		let instance = Instance::new(
 			self.id.into(),
-			self.duration,
+			duration,
 			None,
-			settings.into_internal(self.duration, self.default_loop_start, self.default_track),
+			settings.into_internal(duration, self.default_loop_start, self.default_track),
+			Some(marker_event_sender),
+			Some(event_sender),
+		);
+		let handle = InstanceHandle::new(
+			id,
+			instance.public_state(),
+			instance.public_position(),
+			self.command_sender.clone(),
+			self.markers.clone(),
+			marker_event_receiver,
+			event_receiver,
 		);
-		let handle = InstanceHandle::new(id, instance.public_state(), self.command_sender.clone());
+		(instance, handle)
+	}
+
+	/// Plays the sound with a chain of effects that only
+	/// process the new instance's audio.
+	pub fn play_with_effects(
+		&mut self,
+		settings: InstanceSettings,
+		effects: Vec<(Box<dyn Effect>, EffectSettings)>,
+	) -> Result<InstanceHandle, SoundHandleError> {
+		let id = settings.id;
+		let (mut instance, handle) = self.create_instance(settings);
+		for (effect, effect_settings) in effects {
+			instance.add_effect(effect, effect_settings);
+		}
 		self.command_sender
-			.send(InstanceCommand::Play(id, instance).into())
+			.send(InstanceCommand::Play(id, Box::new(instance)).into())
 			.map_err(|_| SoundHandleError::BackendDisconnected)?;
 		Ok(handle)
 	}
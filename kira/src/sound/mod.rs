@@ -1,21 +1,46 @@
 //! A chunk of audio data.
 
+mod analysis;
+#[cfg(all(
+	feature = "serde_support",
+	any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav")
+))]
+pub mod bank;
+mod channels;
+mod editing;
 pub mod error;
 pub mod handle;
 mod id;
+mod samples;
 mod settings;
+mod waveform;
 
+pub use analysis::Normalization;
+#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+use channels::ChannelOrder;
+pub use channels::ChannelPolicy;
 use error::SoundFromFileError;
 pub use id::SoundId;
+pub use samples::SampleFormat;
+use samples::{SampleSlice, Samples};
+pub(crate) use settings::LoadSettings;
 pub use settings::SoundSettings;
+pub use waveform::WaveformBucket;
+pub(crate) use waveform::WaveformSource;
 
 use crate::{
 	frame::Frame,
 	group::{groups::Groups, GroupId, GroupSet},
+	instance::InstanceLimitBehavior,
 	mixer::TrackIndex,
+	Marker,
 };
 
-use std::fmt::{Debug, Formatter};
+use atomic::Atomic;
+use std::{
+	fmt::{Debug, Formatter},
+	sync::Arc,
+};
 
 #[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
 use std::{fs::File, path::Path};
@@ -25,31 +50,63 @@ use std::{fs::File, path::Path};
 pub struct Sound {
 	id: SoundId,
 	sample_rate: u32,
-	frames: Vec<Frame>,
+	samples: SampleSlice,
 	duration: f64,
 	default_track: TrackIndex,
 	cooldown: Option<f64>,
 	semantic_duration: Option<f64>,
 	default_loop_start: Option<f64>,
 	groups: GroupSet,
+	markers: Vec<Marker>,
+	max_instances: Option<usize>,
+	instance_limit_behavior: InstanceLimitBehavior,
 	cooldown_timer: f64,
+	load_settings: LoadSettings,
+	shared_duration: Option<Arc<Atomic<f64>>>,
 }
 
 impl Sound {
 	/// Creates a new sound from raw sample data.
-	pub fn from_frames(sample_rate: u32, frames: Vec<Frame>, settings: SoundSettings) -> Self {
-		let duration = frames.len() as f64 / sample_rate as f64;
+	///
+	/// If the settings include a [`Normalization`], the frames'
+	/// volume will be adjusted to reach the target level.
+	pub fn from_frames(sample_rate: u32, mut frames: Vec<Frame>, settings: SoundSettings) -> Self {
+		if let Some(normalization) = settings.normalize {
+			if let Some(gain) = normalization.gain(&frames, sample_rate) {
+				for frame in &mut frames {
+					*frame *= gain as f32;
+				}
+			}
+		}
+		let samples = Samples::new(frames, settings.sample_format, settings.collapse_mono);
+		let mut samples = SampleSlice::new(samples);
+		if settings.waveform_overview {
+			samples.build_overview();
+		}
+		Self::from_samples(sample_rate, samples, settings)
+	}
+
+	fn from_samples(sample_rate: u32, samples: SampleSlice, settings: SoundSettings) -> Self {
+		let duration = samples.len() as f64 / sample_rate as f64;
+		let load_settings = settings.load_settings();
+		let mut markers = settings.markers;
+		markers.sort_by(|a, b| a.position.total_cmp(&b.position));
 		Self {
 			id: settings.id,
 			sample_rate,
-			frames,
+			samples,
 			duration,
 			default_track: settings.default_track,
 			cooldown: settings.cooldown,
 			semantic_duration: settings.semantic_duration,
 			default_loop_start: settings.default_loop_start,
 			groups: settings.groups,
+			markers,
+			max_instances: settings.max_instances,
+			instance_limit_behavior: settings.instance_limit_behavior,
 			cooldown_timer: 0.0,
+			load_settings,
+			shared_duration: None,
 		}
 	}
 
@@ -59,57 +116,13 @@ impl Sound {
 	where
 		P: AsRef<Path>,
 	{
-		let mut decoder = minimp3::Decoder::new(File::open(path)?);
-		let mut sample_rate = None;
-		let mut stereo_samples = vec![];
-		loop {
-			match decoder.next_frame() {
-				Ok(frame) => {
-					if let Some(sample_rate) = sample_rate {
-						if sample_rate != frame.sample_rate {
-							return Err(SoundFromFileError::VariableMp3SampleRate);
-						}
-					} else {
-						sample_rate = Some(frame.sample_rate);
-					}
-					match frame.channels {
-						1 => {
-							for sample in frame.data {
-								stereo_samples.push(Frame::from_i32(
-									sample.into(),
-									sample.into(),
-									16,
-								))
-							}
-						}
-						2 => {
-							let mut iter = frame.data.iter();
-							while let (Some(left), Some(right)) = (iter.next(), iter.next()) {
-								stereo_samples.push(Frame::from_i32(
-									(*left).into(),
-									(*right).into(),
-									16,
-								))
-							}
-						}
-						_ => return Err(SoundFromFileError::UnsupportedChannelConfiguration),
-					}
-				}
-				Err(error) => match error {
-					minimp3::Error::Eof => break,
-					error => return Err(error.into()),
-				},
-			}
-		}
-		let sample_rate = match sample_rate {
-			Some(sample_rate) => sample_rate,
-			None => return Err(SoundFromFileError::UnknownMp3SampleRate),
-		};
-		Ok(Self::from_frames(
-			sample_rate as u32,
-			stereo_samples,
-			settings,
-		))
+		let mut frames = vec![];
+		let channel_policy = settings.channel_policy;
+		let sample_rate = decode_mp3(File::open(path)?, |samples| {
+			frames.push(channel_policy.frame(samples, ChannelOrder::Wav)?);
+			Ok(())
+		})?;
+		Ok(Self::from_frames(sample_rate, frames, settings))
 	}
 
 	/// Decodes a sound from an ogg file.
@@ -118,31 +131,7 @@ impl Sound {
 	where
 		P: AsRef<Path>,
 	{
-		use lewton::{inside_ogg::OggStreamReader, samples::Samples};
-		let mut reader = OggStreamReader::new(File::open(path)?)?;
-		let mut stereo_samples = vec![];
-		while let Some(packet) = reader.read_dec_packet_generic::<Vec<Vec<f32>>>()? {
-			let num_channels = packet.len();
-			let num_samples = packet.num_samples();
-			match num_channels {
-				1 => {
-					for i in 0..num_samples {
-						stereo_samples.push(Frame::from_mono(packet[0][i]));
-					}
-				}
-				2 => {
-					for i in 0..num_samples {
-						stereo_samples.push(Frame::new(packet[0][i], packet[1][i]));
-					}
-				}
-				_ => return Err(SoundFromFileError::UnsupportedChannelConfiguration),
-			}
-		}
-		Ok(Self::from_frames(
-			reader.ident_hdr.audio_sample_rate,
-			stereo_samples,
-			settings,
-		))
+		Self::from_ogg_data(File::open(path)?, settings)
 	}
 
 	/// Decodes a sound from an ogg data stream.
@@ -151,31 +140,13 @@ impl Sound {
 	where
 		T: std::io::Read + std::io::Seek,
 	{
-		use lewton::{inside_ogg::OggStreamReader, samples::Samples};
-		let mut reader = OggStreamReader::new(data)?;
-		let mut stereo_samples = vec![];
-		while let Some(packet) = reader.read_dec_packet_generic::<Vec<Vec<f32>>>()? {
-			let num_channels = packet.len();
-			let num_samples = packet.num_samples();
-			match num_channels {
-				1 => {
-					for i in 0..num_samples {
-						stereo_samples.push(Frame::from_mono(packet[0][i]));
-					}
-				}
-				2 => {
-					for i in 0..num_samples {
-						stereo_samples.push(Frame::new(packet[0][i], packet[1][i]));
-					}
-				}
-				_ => return Err(SoundFromFileError::UnsupportedChannelConfiguration),
-			}
-		}
-		Ok(Self::from_frames(
-			reader.ident_hdr.audio_sample_rate,
-			stereo_samples,
-			settings,
-		))
+		let mut frames = vec![];
+		let channel_policy = settings.channel_policy;
+		let sample_rate = decode_ogg(data, |samples| {
+			frames.push(channel_policy.frame(samples, ChannelOrder::Vorbis)?);
+			Ok(())
+		})?;
+		Ok(Self::from_frames(sample_rate, frames, settings))
 	}
 
 	/// Decodes a sound from a flac file.
@@ -184,33 +155,13 @@ impl Sound {
 	where
 		P: AsRef<Path>,
 	{
-		let mut reader = claxon::FlacReader::open(path)?;
-		let streaminfo = reader.streaminfo();
-		let mut stereo_samples = vec![];
-		match reader.streaminfo().channels {
-			1 => {
-				for sample in reader.samples() {
-					let sample = sample?;
-					stereo_samples.push(Frame::from_i32(
-						sample,
-						sample,
-						streaminfo.bits_per_sample,
-					));
-				}
-			}
-			2 => {
-				let mut iter = reader.samples();
-				while let (Some(left), Some(right)) = (iter.next(), iter.next()) {
-					stereo_samples.push(Frame::from_i32(left?, right?, streaminfo.bits_per_sample));
-				}
-			}
-			_ => return Err(SoundFromFileError::UnsupportedChannelConfiguration),
-		}
-		Ok(Self::from_frames(
-			streaminfo.sample_rate,
-			stereo_samples,
-			settings,
-		))
+		let mut frames = vec![];
+		let channel_policy = settings.channel_policy;
+		let sample_rate = decode_flac(path.as_ref(), |samples| {
+			frames.push(channel_policy.frame(samples, ChannelOrder::Wav)?);
+			Ok(())
+		})?;
+		Ok(Self::from_frames(sample_rate, frames, settings))
 	}
 
 	/// Decodes a sound from a wav file.
@@ -219,52 +170,13 @@ impl Sound {
 	where
 		P: AsRef<Path>,
 	{
-		let mut reader = hound::WavReader::open(path)?;
-		let spec = reader.spec();
-		let mut stereo_samples = vec![];
-		match reader.spec().channels {
-			1 => match spec.sample_format {
-				hound::SampleFormat::Float => {
-					for sample in reader.samples::<f32>() {
-						stereo_samples.push(Frame::from_mono(sample?))
-					}
-				}
-				hound::SampleFormat::Int => {
-					for sample in reader.samples::<i32>() {
-						let sample = sample?;
-						stereo_samples.push(Frame::from_i32(
-							sample,
-							sample,
-							spec.bits_per_sample.into(),
-						));
-					}
-				}
-			},
-			2 => match spec.sample_format {
-				hound::SampleFormat::Float => {
-					let mut iter = reader.samples::<f32>();
-					while let (Some(left), Some(right)) = (iter.next(), iter.next()) {
-						stereo_samples.push(Frame::new(left?, right?));
-					}
-				}
-				hound::SampleFormat::Int => {
-					let mut iter = reader.samples::<i32>();
-					while let (Some(left), Some(right)) = (iter.next(), iter.next()) {
-						stereo_samples.push(Frame::from_i32(
-							left?,
-							right?,
-							spec.bits_per_sample.into(),
-						));
-					}
-				}
-			},
-			_ => return Err(SoundFromFileError::UnsupportedChannelConfiguration),
-		}
-		Ok(Self::from_frames(
-			reader.spec().sample_rate,
-			stereo_samples,
-			settings,
-		))
+		let mut frames = vec![];
+		let channel_policy = settings.channel_policy;
+		let sample_rate = decode_wav(path.as_ref(), |samples| {
+			frames.push(channel_policy.frame(samples, ChannelOrder::Wav)?);
+			Ok(())
+		})?;
+		Ok(Self::from_frames(sample_rate, frames, settings))
 	}
 
 	/// Decodes a sound from a file.
@@ -293,6 +205,72 @@ impl Sound {
 		Err(SoundFromFileError::UnsupportedAudioFileFormat)
 	}
 
+	/// Decodes a file and creates a separate mono sound
+	/// for each of its channels.
+	///
+	/// The audio format will be automatically determined from the file extension.
+	/// The channel policy in the settings is not used, and each sound is given
+	/// a new unique ID, so the ID in the settings is not used either.
+	#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+	pub fn split_channels_from_file<P>(
+		path: P,
+		settings: SoundSettings,
+	) -> Result<Vec<Self>, SoundFromFileError>
+	where
+		P: AsRef<Path>,
+	{
+		let mut channels: Vec<Vec<Frame>> = vec![];
+		let mut on_frame = |samples: &[f32]| {
+			if channels.is_empty() {
+				channels.resize_with(samples.len(), Vec::new);
+			}
+			for (channel, sample) in channels.iter_mut().zip(samples) {
+				channel.push(Frame::from_mono(*sample));
+			}
+			Ok(())
+		};
+		let path = path.as_ref();
+		let extension = path.extension().and_then(|extension| extension.to_str());
+		let sample_rate = match extension {
+			#[cfg(feature = "mp3")]
+			Some("mp3") => decode_mp3(File::open(path)?, &mut on_frame)?,
+			#[cfg(feature = "ogg")]
+			Some("ogg") => decode_ogg(File::open(path)?, &mut on_frame)?,
+			#[cfg(feature = "flac")]
+			Some("flac") => decode_flac(path, &mut on_frame)?,
+			#[cfg(feature = "wav")]
+			Some("wav") => decode_wav(path, &mut on_frame)?,
+			_ => return Err(SoundFromFileError::UnsupportedAudioFileFormat),
+		};
+		Ok(channels
+			.into_iter()
+			.map(|frames| {
+				Self::from_frames(sample_rate, frames, settings.clone().id(SoundId::new()))
+			})
+			.collect())
+	}
+
+	/// Creates a new sound from the section of this sound
+	/// between `start` and `end` (in seconds).
+	///
+	/// The new sound shares this sound's sample data, so no
+	/// audio is copied. It has its own ID, settings, and
+	/// cooldown timer. The range is clamped to the duration
+	/// of this sound, and the settings that only affect loading
+	/// (`channel_policy`, `sample_format`, `collapse_mono`,
+	/// `normalize`, and `waveform_overview`) are ignored.
+	/// If this sound has a waveform overview, the new sound
+	/// will share it.
+	pub fn slice(&self, start: f64, end: f64, settings: SoundSettings) -> Self {
+		let start_frame = (start.max(0.0) * self.sample_rate as f64).round() as usize;
+		let end_frame = (end.max(0.0) * self.sample_rate as f64).round() as usize;
+		Self::from_samples(
+			self.sample_rate,
+			self.samples.slice(start_frame, end_frame),
+			settings.with_load_settings(self.load_settings),
+		)
+	}
+
 	/// Gets the unique identifier for this sound.
 	pub fn id(&self) -> SoundId {
 		self.id
@@ -325,32 +303,69 @@ impl Sound {
 		self.default_loop_start
 	}
 
+	/// Gets the markers of this sound, sorted by position.
+	pub fn markers(&self) -> &[Marker] {
+		&self.markers
+	}
+
+	/// Gets the highest absolute sample value in either channel
+	/// of the sound, where 1.0 is full scale.
+	pub fn peak(&self) -> f64 {
+		analysis::peak(self.samples.frames())
+	}
+
+	/// Gets the root mean square of the samples in both
+	/// channels of the sound.
+	pub fn rms(&self) -> f64 {
+		analysis::rms(self.samples.frames())
+	}
+
+	/// Gets the integrated loudness of the sound (in LUFS) as
+	/// defined by ITU-R BS.1770 and EBU R128.
+	///
+	/// Returns negative infinity if the sound is silent or
+	/// shorter than 400 milliseconds.
+	pub fn loudness(&self) -> f64 {
+		analysis::loudness(self.samples.frames(), self.sample_rate)
+	}
+
+	/// Gets the maximum number of instances of this sound
+	/// that can play at the same time.
+	pub fn max_instances(&self) -> Option<usize> {
+		self.max_instances
+	}
+
+	/// Gets what happens when the sound is played while the
+	/// maximum number of instances are already playing.
+	pub fn instance_limit_behavior(&self) -> InstanceLimitBehavior {
+		self.instance_limit_behavior
+	}
+
+	/// Summarizes the audio between `start` and `end`
+	/// (in seconds) for drawing a waveform.
+	///
+	/// The time range is divided into `num_buckets` equal
+	/// spans, and a [`WaveformBucket`] is returned for each one.
+	/// Spans outside of the sound are silent.
+	pub fn waveform(&self, start: f64, end: f64, num_buckets: usize) -> Vec<WaveformBucket> {
+		self.waveform_source().waveform(start, end, num_buckets)
+	}
+
+	/// Returns the sample data of the sound for drawing waveforms.
+	pub(crate) fn waveform_source(&self) -> WaveformSource {
+		WaveformSource::new(self.sample_rate, self.samples.clone())
+	}
+
 	/// Gets the frame of this sound at an arbitrary time
 	/// in seconds, interpolating between samples if necessary.
 	pub fn get_frame_at_position(&self, position: f64) -> Frame {
 		let sample_position = self.sample_rate as f64 * position;
 		let x = (sample_position % 1.0) as f32;
 		let current_sample_index = sample_position as usize;
-		let y0 = if current_sample_index == 0 {
-			Frame::from_mono(0.0)
-		} else {
-			*self
-				.frames
-				.get(current_sample_index - 1)
-				.unwrap_or(&Frame::from_mono(0.0))
-		};
-		let y1 = *self
-			.frames
-			.get(current_sample_index)
-			.unwrap_or(&Frame::from_mono(0.0));
-		let y2 = *self
-			.frames
-			.get(current_sample_index + 1)
-			.unwrap_or(&Frame::from_mono(0.0));
-		let y3 = *self
-			.frames
-			.get(current_sample_index + 2)
-			.unwrap_or(&Frame::from_mono(0.0));
+		let mut frames = [Frame::from_mono(0.0); 4];
+		self.samples
+			.fill(current_sample_index as isize - 1, &mut frames);
+		let [y0, y1, y2, y3] = frames;
 		let c0 = y1;
 		let c1 = (y2 - y0) * 0.5;
 		let c2 = y0 - y1 * 2.5 + y2 * 2.0 - y3 * 0.5;
@@ -358,6 +373,32 @@ impl Sound {
 		((c3 * x + c2) * x + c1) * x + c0
 	}
 
+	/// Gets the settings that affect how the sound's audio
+	/// was decoded and stored.
+	pub(crate) fn load_settings(&self) -> LoadSettings {
+		self.load_settings
+	}
+
+	/// Sets a value that should be updated with the sound's
+	/// duration whenever it changes.
+	pub(crate) fn share_duration(&mut self, duration: Arc<Atomic<f64>>) {
+		self.shared_duration = Some(duration);
+	}
+
+	/// Replaces this sound's audio with another sound's audio,
+	/// leaving the other sound with the old audio.
+	///
+	/// The sound's ID and settings are kept.
+	#[cfg(any(feature = "mp3", feature = "ogg", feature = "flac", feature = "wav"))]
+	pub(crate) fn swap_audio(&mut self, other: &mut Sound) {
+		std::mem::swap(&mut self.sample_rate, &mut other.sample_rate);
+		std::mem::swap(&mut self.samples, &mut other.samples);
+		std::mem::swap(&mut self.duration, &mut other.duration);
+		if let Some(shared_duration) = &self.shared_duration {
+			shared_duration.store(self.duration, atomic::Ordering::Relaxed);
+		}
+	}
+
 	/// Starts the cooldown timer for the sound.
 	pub(crate) fn start_cooldown(&mut self) {
 		if let Some(cooldown) = self.cooldown {
@@ -388,7 +429,7 @@ impl Sound {
 
 impl Debug for Sound {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		f.debug_struct(&format!("Sound ({} frames)", self.frames.len()))
+		f.debug_struct(&format!("Sound ({} frames)", self.samples.len()))
 			.field("sample_rate", &self.sample_rate)
 			.field("duration", &self.duration)
 			.field("default_track", &self.default_track)
@@ -396,7 +437,132 @@ impl Debug for Sound {
 			.field("semantic_duration", &self.semantic_duration)
 			.field("default_loop_start", &self.default_loop_start)
 			.field("groups", &self.groups)
+			.field("markers", &self.markers)
+			.field("max_instances", &self.max_instances)
+			.field("instance_limit_behavior", &self.instance_limit_behavior)
 			.field("cooldown_timer", &self.cooldown_timer)
 			.finish()
 	}
 }
+
+/// Decodes an mp3 data stream, calling `on_frame` with the samples
+/// for each channel of every frame. Returns the sample rate.
+#[cfg(feature = "mp3")]
+fn decode_mp3<T>(
+	data: T,
+	mut on_frame: impl FnMut(&[f32]) -> Result<(), SoundFromFileError>,
+) -> Result<u32, SoundFromFileError>
+where
+	T: std::io::Read,
+{
+	let mut decoder = minimp3::Decoder::new(data);
+	let mut sample_rate = None;
+	let mut samples = vec![];
+	let scale = Frame::int_sample_scale(16);
+	loop {
+		match decoder.next_frame() {
+			Ok(frame) => {
+				if let Some(sample_rate) = sample_rate {
+					if sample_rate != frame.sample_rate {
+						return Err(SoundFromFileError::VariableMp3SampleRate);
+					}
+				} else {
+					sample_rate = Some(frame.sample_rate);
+				}
+				for frame_samples in frame.data.chunks_exact(frame.channels) {
+					samples.clear();
+					samples.extend(frame_samples.iter().map(|sample| *sample as f32 * scale));
+					on_frame(&samples)?;
+				}
+			}
+			Err(error) => match error {
+				minimp3::Error::Eof => break,
+				error => return Err(error.into()),
+			},
+		}
+	}
+	match sample_rate {
+		Some(sample_rate) => Ok(sample_rate as u32),
+		None => Err(SoundFromFileError::UnknownMp3SampleRate),
+	}
+}
+
+/// Decodes an ogg data stream, calling `on_frame` with the samples
+/// for each channel of every frame. Returns the sample rate.
+#[cfg(feature = "ogg")]
+fn decode_ogg<T>(
+	data: T,
+	mut on_frame: impl FnMut(&[f32]) -> Result<(), SoundFromFileError>,
+) -> Result<u32, SoundFromFileError>
+where
+	T: std::io::Read + std::io::Seek,
+{
+	use lewton::{inside_ogg::OggStreamReader, samples::Samples};
+	let mut reader = OggStreamReader::new(data)?;
+	let mut samples = vec![];
+	while let Some(packet) = reader.read_dec_packet_generic::<Vec<Vec<f32>>>()? {
+		for i in 0..packet.num_samples() {
+			samples.clear();
+			samples.extend(packet.iter().map(|channel| channel[i]));
+			on_frame(&samples)?;
+		}
+	}
+	Ok(reader.ident_hdr.audio_sample_rate)
+}
+
+/// Decodes a flac file, calling `on_frame` with the samples
+/// for each channel of every frame. Returns the sample rate.
+#[cfg(feature = "flac")]
+fn decode_flac(
+	path: &Path,
+	mut on_frame: impl FnMut(&[f32]) -> Result<(), SoundFromFileError>,
+) -> Result<u32, SoundFromFileError> {
+	let mut reader = claxon::FlacReader::open(path)?;
+	let streaminfo = reader.streaminfo();
+	let num_channels = streaminfo.channels as usize;
+	let scale = Frame::int_sample_scale(streaminfo.bits_per_sample);
+	let mut samples = Vec::with_capacity(num_channels);
+	for sample in reader.samples() {
+		samples.push(sample? as f32 * scale);
+		if samples.len() == num_channels {
+			on_frame(&samples)?;
+			samples.clear();
+		}
+	}
+	Ok(streaminfo.sample_rate)
+}
+
+/// Decodes a wav file, calling `on_frame` with the samples
+/// for each channel of every frame. Returns the sample rate.
+#[cfg(feature = "wav")]
+pub(crate) fn decode_wav(
+	path: &Path,
+	mut on_frame: impl FnMut(&[f32]) -> Result<(), SoundFromFileError>,
+) -> Result<u32, SoundFromFileError> {
+	let mut reader = hound::WavReader::open(path)?;
+	let spec = reader.spec();
+	let num_channels = spec.channels as usize;
+	let mut samples = Vec::with_capacity(num_channels);
+	match spec.sample_format {
+		hound::SampleFormat::Float => {
+			for sample in reader.samples::<f32>() {
+				samples.push(sample?);
+				if samples.len() == num_channels {
+					on_frame(&samples)?;
+					samples.clear();
+				}
+			}
+		}
+		hound::SampleFormat::Int => {
+			let scale = Frame::int_sample_scale(spec.bits_per_sample.into());
+			for sample in reader.samples::<i32>() {
+				samples.push(sample? as f32 * scale);
+				if samples.len() == num_channels {
+					on_frame(&samples)?;
+					samples.clear();
+				}
+			}
+		}
+	}
+	Ok(spec.sample_rate)
+}
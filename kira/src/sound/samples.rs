@@ -0,0 +1,659 @@
+use std::{
+	ops::Range,
+	sync::{Arc, Mutex},
+};
+
+use crate::Frame;
+
+use super::waveform::{summarize_frames, Summary, WaveformOverview};
+
+/// How the samples of a sound are stored in memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum SampleFormat {
+	/// Each sample is stored as a 32-bit float.
+	F32,
+	/// Each sample is stored as a 16-bit integer.
+	///
+	/// This uses half the memory of [`SampleFormat::F32`]
+	/// and doesn't lose any quality for sounds loaded
+	/// from 16-bit audio files.
+	I16,
+	/// Samples are compressed to 4 bits each using
+	/// IMA ADPCM encoding.
+	///
+	/// This uses about a sixth of the memory of
+	/// [`SampleFormat::F32`], but the sound quality is
+	/// noticeably lower, and decoding samples takes
+	/// more processing time.
+	Adpcm,
+}
+
+impl Default for SampleFormat {
+	fn default() -> Self {
+		Self::F32
+	}
+}
+
+const ADPCM_BLOCK_SIZE: usize = 32;
+
+const ADPCM_INDEX_TABLE: [i32; 8] = [-1, -1, -1, -1, 2, 4, 6, 8];
+
+const ADPCM_STEP_TABLE: [i32; 89] = [
+	7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66,
+	73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449,
+	494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272,
+	2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630, 9493,
+	10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794, 32767,
+];
+
+fn f32_to_i16(sample: f32) -> i16 {
+	(sample * 32768.0)
+		.round()
+		.max(i16::MIN as f32)
+		.min(i16::MAX as f32) as i16
+}
+
+fn i16_to_f32(sample: i16) -> f32 {
+	sample as f32 / 32768.0
+}
+
+/// The state of an IMA ADPCM encoder or decoder.
+#[derive(Debug, Copy, Clone)]
+struct AdpcmState {
+	predictor: i32,
+	step_index: i32,
+}
+
+impl AdpcmState {
+	/// Updates the state with a 4-bit code and returns
+	/// the decoded sample.
+	fn decode(&mut self, code: u8) -> i16 {
+		let step = ADPCM_STEP_TABLE[self.step_index as usize];
+		let mut difference = step >> 3;
+		if code & 4 != 0 {
+			difference += step;
+		}
+		if code & 2 != 0 {
+			difference += step >> 1;
+		}
+		if code & 1 != 0 {
+			difference += step >> 2;
+		}
+		if code & 8 != 0 {
+			self.predictor -= difference;
+		} else {
+			self.predictor += difference;
+		}
+		self.predictor = self.predictor.max(i16::MIN as i32).min(i16::MAX as i32);
+		self.step_index = (self.step_index + ADPCM_INDEX_TABLE[(code & 7) as usize])
+			.max(0)
+			.min(ADPCM_STEP_TABLE.len() as i32 - 1);
+		self.predictor as i16
+	}
+
+	/// Finds the 4-bit code that best approximates a sample
+	/// and updates the state as if that code was decoded.
+	fn encode(&mut self, sample: i16) -> u8 {
+		let step = ADPCM_STEP_TABLE[self.step_index as usize];
+		let mut difference = sample as i32 - self.predictor;
+		let mut code = 0;
+		if difference < 0 {
+			code = 8;
+			difference = -difference;
+		}
+		let mut mask = 4;
+		let mut step_part = step;
+		for _ in 0..3 {
+			if difference >= step_part {
+				code |= mask;
+				difference -= step_part;
+			}
+			mask >>= 1;
+			step_part >>= 1;
+		}
+		self.decode(code);
+		code
+	}
+}
+
+/// The most recently decoded block of an ADPCM channel.
+#[derive(Debug)]
+struct DecodedBlock {
+	index: Option<usize>,
+	samples: [f32; ADPCM_BLOCK_SIZE],
+}
+
+/// One channel of IMA ADPCM encoded audio.
+///
+/// The samples are split into blocks, and the state of the
+/// encoder at the start of each block is stored so that
+/// decoding can start from any block.
+#[derive(Debug)]
+pub(crate) struct AdpcmChannel {
+	block_states: Vec<AdpcmState>,
+	codes: Vec<u8>,
+	len: usize,
+	/// Instances read frames close to the ones they read
+	/// last, so the last decoded block is kept around.
+	cache: Box<Mutex<DecodedBlock>>,
+}
+
+impl AdpcmChannel {
+	fn encode(samples: impl Iterator<Item = f32>) -> Self {
+		let mut state = AdpcmState {
+			predictor: 0,
+			step_index: 0,
+		};
+		let mut block_states = vec![];
+		let mut codes = vec![];
+		let mut len = 0;
+		for (i, sample) in samples.enumerate() {
+			if i % ADPCM_BLOCK_SIZE == 0 {
+				block_states.push(state);
+			}
+			let code = state.encode(f32_to_i16(sample));
+			if i % 2 == 0 {
+				codes.push(code);
+			} else {
+				*codes.last_mut().unwrap() |= code << 4;
+			}
+			len += 1;
+		}
+		Self::from_parts(block_states, codes, len)
+	}
+
+	fn from_parts(block_states: Vec<AdpcmState>, codes: Vec<u8>, len: usize) -> Self {
+		Self {
+			block_states,
+			codes,
+			len,
+			cache: Box::new(Mutex::new(DecodedBlock {
+				index: None,
+				samples: [0.0; ADPCM_BLOCK_SIZE],
+			})),
+		}
+	}
+
+	fn code(&self, index: usize) -> u8 {
+		let byte = self.codes[index / 2];
+		if index % 2 == 0 {
+			byte & 0xf
+		} else {
+			byte >> 4
+		}
+	}
+
+	fn decode_block(&self, block: usize, out: &mut [f32; ADPCM_BLOCK_SIZE]) {
+		let mut state = self.block_states[block];
+		let start = block * ADPCM_BLOCK_SIZE;
+		let end = (start + ADPCM_BLOCK_SIZE).min(self.len);
+		for (index, sample) in (start..end).zip(out.iter_mut()) {
+			*sample = i16_to_f32(state.decode(self.code(index)));
+		}
+	}
+
+	/// Calls `f` with the decoded samples of a block, only
+	/// decoding the block if it wasn't the last one decoded.
+	fn with_block<T>(&self, block: usize, f: impl FnOnce(&[f32; ADPCM_BLOCK_SIZE]) -> T) -> T {
+		// if another thread is reading this channel, decode the
+		// block separately instead of waiting for the cache
+		match self.cache.try_lock() {
+			Ok(mut cache) => {
+				if cache.index != Some(block) {
+					self.decode_block(block, &mut cache.samples);
+					cache.index = Some(block);
+				}
+				f(&cache.samples)
+			}
+			Err(_) => {
+				let mut samples = [0.0; ADPCM_BLOCK_SIZE];
+				self.decode_block(block, &mut samples);
+				f(&samples)
+			}
+		}
+	}
+}
+
+impl Clone for AdpcmChannel {
+	fn clone(&self) -> Self {
+		Self::from_parts(self.block_states.clone(), self.codes.clone(), self.len)
+	}
+}
+
+/// Returns each ADPCM block overlapping the frames from `start`
+/// to `end` (exclusive), along with the frames in the block
+/// that are in that range.
+fn adpcm_blocks(start: usize, end: usize) -> impl Iterator<Item = (usize, Range<usize>)> {
+	(start / ADPCM_BLOCK_SIZE..(end + ADPCM_BLOCK_SIZE - 1) / ADPCM_BLOCK_SIZE).map(move |block| {
+		let block_start = block * ADPCM_BLOCK_SIZE;
+		(
+			block,
+			block_start.max(start)..(block_start + ADPCM_BLOCK_SIZE).min(end),
+		)
+	})
+}
+
+/// The sample data of a sound.
+#[derive(Debug, Clone)]
+pub(crate) enum Samples {
+	Stereo(Vec<Frame>),
+	Mono(Vec<f32>),
+	/// Left and right samples, interleaved.
+	StereoI16(Vec<i16>),
+	MonoI16(Vec<i16>),
+	StereoAdpcm(AdpcmChannel, AdpcmChannel),
+	MonoAdpcm(AdpcmChannel),
+}
+
+impl Samples {
+	/// Stores frames in the given format.
+	///
+	/// If `collapse_mono` is `true` and every frame's left and
+	/// right samples are the same, only one channel will be stored.
+	pub fn new(frames: Vec<Frame>, format: SampleFormat, collapse_mono: bool) -> Self {
+		let mono = collapse_mono && frames.iter().all(|frame| frame.left == frame.right);
+		match (format, mono) {
+			(SampleFormat::F32, false) => Self::Stereo(frames),
+			(SampleFormat::F32, true) => {
+				Self::Mono(frames.iter().map(|frame| frame.left).collect())
+			}
+			(SampleFormat::I16, false) => Self::StereoI16(
+				frames
+					.iter()
+					.flat_map(|frame| {
+						std::iter::once(f32_to_i16(frame.left))
+							.chain(std::iter::once(f32_to_i16(frame.right)))
+					})
+					.collect(),
+			),
+			(SampleFormat::I16, true) => {
+				Self::MonoI16(frames.iter().map(|frame| f32_to_i16(frame.left)).collect())
+			}
+			(SampleFormat::Adpcm, false) => Self::StereoAdpcm(
+				AdpcmChannel::encode(frames.iter().map(|frame| frame.left)),
+				AdpcmChannel::encode(frames.iter().map(|frame| frame.right)),
+			),
+			(SampleFormat::Adpcm, true) => {
+				Self::MonoAdpcm(AdpcmChannel::encode(frames.iter().map(|frame| frame.left)))
+			}
+		}
+	}
+
+	/// Gets the format the samples are stored in.
+	pub fn format(&self) -> SampleFormat {
+		match self {
+			Samples::Stereo(_) | Samples::Mono(_) => SampleFormat::F32,
+			Samples::StereoI16(_) | Samples::MonoI16(_) => SampleFormat::I16,
+			Samples::StereoAdpcm(..) | Samples::MonoAdpcm(_) => SampleFormat::Adpcm,
+		}
+	}
+
+	/// Gets the number of frames.
+	pub fn len(&self) -> usize {
+		match self {
+			Samples::Stereo(frames) => frames.len(),
+			Samples::Mono(samples) => samples.len(),
+			Samples::StereoI16(samples) => samples.len() / 2,
+			Samples::MonoI16(samples) => samples.len(),
+			Samples::StereoAdpcm(left, _) => left.len,
+			Samples::MonoAdpcm(channel) => channel.len,
+		}
+	}
+
+	/// Calls `f` with the index and value of each frame
+	/// from `start` to `end` (exclusive).
+	pub fn for_each_frame(&self, start: usize, end: usize, mut f: impl FnMut(usize, Frame)) {
+		match self {
+			Samples::Stereo(frames) => {
+				for (index, frame) in frames[start..end].iter().enumerate() {
+					f(start + index, *frame);
+				}
+			}
+			Samples::Mono(samples) => {
+				for (index, sample) in samples[start..end].iter().enumerate() {
+					f(start + index, Frame::from_mono(*sample));
+				}
+			}
+			Samples::StereoI16(samples) => {
+				for (index, pair) in samples[start * 2..end * 2].chunks(2).enumerate() {
+					f(
+						start + index,
+						Frame::new(i16_to_f32(pair[0]), i16_to_f32(pair[1])),
+					);
+				}
+			}
+			Samples::MonoI16(samples) => {
+				for (index, sample) in samples[start..end].iter().enumerate() {
+					f(start + index, Frame::from_mono(i16_to_f32(*sample)));
+				}
+			}
+			Samples::StereoAdpcm(left, right) => {
+				for (block, range) in adpcm_blocks(start, end) {
+					left.with_block(block, |left| {
+						right.with_block(block, |right| {
+							for index in range {
+								let offset = index % ADPCM_BLOCK_SIZE;
+								f(index, Frame::new(left[offset], right[offset]));
+							}
+						})
+					});
+				}
+			}
+			Samples::MonoAdpcm(channel) => {
+				for (block, range) in adpcm_blocks(start, end) {
+					channel.with_block(block, |samples| {
+						for index in range {
+							f(index, Frame::from_mono(samples[index % ADPCM_BLOCK_SIZE]));
+						}
+					});
+				}
+			}
+		}
+	}
+}
+
+/// A range of frames from sample data that may be shared
+/// between multiple sounds.
+#[derive(Debug, Clone)]
+pub(crate) struct SampleSlice {
+	samples: Arc<Samples>,
+	overview: Option<Arc<WaveformOverview>>,
+	offset: usize,
+	len: usize,
+}
+
+impl SampleSlice {
+	pub fn new(samples: Samples) -> Self {
+		Self {
+			len: samples.len(),
+			samples: Arc::new(samples),
+			overview: None,
+			offset: 0,
+		}
+	}
+
+	/// Gets the number of frames.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Gets the format the samples are stored in.
+	pub fn format(&self) -> SampleFormat {
+		self.samples.format()
+	}
+
+	/// Gets whether a waveform overview has been built
+	/// for the sample data.
+	pub fn has_overview(&self) -> bool {
+		self.overview.is_some()
+	}
+
+	/// Builds a waveform overview of the sample data, which
+	/// speeds up summarizing long ranges of frames.
+	pub fn build_overview(&mut self) {
+		self.overview = Some(Arc::new(WaveformOverview::new(&self.samples)));
+	}
+
+	/// Summarizes the frames from `start` to `end` (exclusive)
+	/// of this slice.
+	///
+	/// The range is clamped to the frames of this slice.
+	pub fn summarize(&self, start: usize, end: usize) -> Summary {
+		let end = end.min(self.len);
+		let start = start.min(end);
+		let (start, end) = (self.offset + start, self.offset + end);
+		match &self.overview {
+			Some(overview) => overview.summarize(&self.samples, start, end),
+			None => summarize_frames(&self.samples, start, end),
+		}
+	}
+
+	/// Returns a slice sharing the same sample data containing the
+	/// frames from `start` to `end` (exclusive) of this slice.
+	///
+	/// The range is clamped to the frames of this slice.
+	pub fn slice(&self, start: usize, end: usize) -> Self {
+		let end = end.min(self.len);
+		let start = start.min(end);
+		Self {
+			samples: self.samples.clone(),
+			overview: self.overview.clone(),
+			offset: self.offset + start,
+			len: end - start,
+		}
+	}
+
+	/// Returns an iterator over every frame of the slice.
+	///
+	/// The frames are decoded a chunk at a time.
+	pub fn frames(&self) -> impl Iterator<Item = Frame> + '_ {
+		const CHUNK_SIZE: usize = 4096;
+		(0..self.len).step_by(CHUNK_SIZE).flat_map(move |start| {
+			let mut chunk = vec![Frame::from_mono(0.0); CHUNK_SIZE.min(self.len - start)];
+			self.fill(start as isize, &mut chunk);
+			chunk
+		})
+	}
+
+	/// Decodes every frame of the slice.
+	pub fn to_frames(&self) -> Vec<Frame> {
+		let mut frames = Vec::with_capacity(self.len);
+		self.samples
+			.for_each_frame(self.offset, self.offset + self.len, |_, frame| {
+				frames.push(frame)
+			});
+		frames
+	}
+
+	/// Fills `out` with consecutive frames beginning at `start`.
+	/// Frames outside of the slice will be silent.
+	pub fn fill(&self, start: isize, out: &mut [Frame]) {
+		for frame in out.iter_mut() {
+			*frame = Frame::from_mono(0.0);
+		}
+		let range_start = start.max(0) as usize;
+		let range_end = (start + out.len() as isize).max(0) as usize;
+		let range_end = range_end.min(self.len);
+		if range_start >= range_end {
+			return;
+		}
+		self.samples.for_each_frame(
+			self.offset + range_start,
+			self.offset + range_end,
+			|index, frame| {
+				out[(index as isize - self.offset as isize - start) as usize] = frame;
+			},
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::f32::consts::PI;
+
+	use super::*;
+
+	/// Generates a stereo sine wave with a different frequency
+	/// in each channel.
+	fn sine(len: usize) -> Vec<Frame> {
+		(0..len)
+			.map(|i| {
+				let time = i as f32 / 48000.0;
+				Frame::new(
+					0.5 * (2.0 * PI * 440.0 * time).sin(),
+					0.25 * (2.0 * PI * 660.0 * time).sin(),
+				)
+			})
+			.collect()
+	}
+
+	fn decode(samples: &Samples, start: usize, end: usize) -> Vec<Frame> {
+		let mut frames = vec![];
+		samples.for_each_frame(start, end, |index, frame| {
+			assert_eq!(index, start + frames.len());
+			frames.push(frame);
+		});
+		frames
+	}
+
+	#[test]
+	fn i16_conversion() {
+		assert_eq!(f32_to_i16(0.0), 0);
+		assert_eq!(f32_to_i16(-1.0), i16::MIN);
+		assert_eq!(f32_to_i16(1.0), i16::MAX);
+		assert_eq!(f32_to_i16(2.0), i16::MAX);
+		assert_eq!(f32_to_i16(-2.0), i16::MIN);
+		let frames = sine(1000);
+		let samples = Samples::new(frames.clone(), SampleFormat::I16, true);
+		assert_eq!(samples.format(), SampleFormat::I16);
+		assert_eq!(samples.len(), frames.len());
+		for (decoded, original) in decode(&samples, 0, frames.len()).iter().zip(&frames) {
+			assert!((decoded.left - original.left).abs() <= 0.5 / 32768.0);
+			assert!((decoded.right - original.right).abs() <= 0.5 / 32768.0);
+		}
+	}
+
+	#[test]
+	fn identical_channels_are_collapsed_to_mono() {
+		let frames: Vec<Frame> = sine(100)
+			.iter()
+			.map(|frame| Frame::from_mono(frame.left))
+			.collect();
+		for &format in &[SampleFormat::F32, SampleFormat::I16, SampleFormat::Adpcm] {
+			let mono = Samples::new(frames.clone(), format, true);
+			assert!(matches!(
+				mono,
+				Samples::Mono(_) | Samples::MonoI16(_) | Samples::MonoAdpcm(_)
+			));
+			assert_eq!(mono.format(), format);
+			assert_eq!(mono.len(), frames.len());
+			for frame in decode(&mono, 0, frames.len()) {
+				assert_eq!(frame.left, frame.right);
+			}
+			let stereo = Samples::new(frames.clone(), format, false);
+			assert!(matches!(
+				stereo,
+				Samples::Stereo(_) | Samples::StereoI16(_) | Samples::StereoAdpcm(..)
+			));
+			// different channels are never collapsed
+			let stereo = Samples::new(sine(100), format, true);
+			assert!(matches!(
+				stereo,
+				Samples::Stereo(_) | Samples::StereoI16(_) | Samples::StereoAdpcm(..)
+			));
+		}
+	}
+
+	#[test]
+	fn adpcm_round_trip_error_is_bounded() {
+		let frames = sine(48000);
+		let samples = Samples::new(frames.clone(), SampleFormat::Adpcm, true);
+		assert_eq!(samples.len(), frames.len());
+		// the encoder starts with the smallest step size, so it
+		// takes a few samples to catch up with a loud sound
+		let max_error = decode(&samples, ADPCM_BLOCK_SIZE, frames.len())
+			.iter()
+			.zip(&frames[ADPCM_BLOCK_SIZE..])
+			.map(|(decoded, original)| {
+				(decoded.left - original.left)
+					.abs()
+					.max((decoded.right - original.right).abs())
+			})
+			.fold(0.0, f32::max);
+		assert!(max_error < 0.01, "{}", max_error);
+	}
+
+	#[test]
+	fn adpcm_ranges_across_block_boundaries() {
+		// a partial block at the end
+		let len = ADPCM_BLOCK_SIZE * 4 + 5;
+		let samples = Samples::new(sine(len), SampleFormat::Adpcm, true);
+		let all = decode(&samples, 0, len);
+		assert_eq!(all.len(), len);
+		let boundaries = [
+			0,
+			1,
+			ADPCM_BLOCK_SIZE - 1,
+			ADPCM_BLOCK_SIZE,
+			ADPCM_BLOCK_SIZE + 1,
+			ADPCM_BLOCK_SIZE * 2,
+			ADPCM_BLOCK_SIZE * 4,
+			len - 1,
+			len,
+		];
+		for &start in &boundaries {
+			for &end in boundaries.iter().filter(|&&end| end >= start) {
+				assert_eq!(decode(&samples, start, end), &all[start..end]);
+			}
+		}
+	}
+
+	#[test]
+	fn adpcm_block_cache() {
+		let channel = AdpcmChannel::encode(sine(ADPCM_BLOCK_SIZE * 3).iter().map(|f| f.left));
+		let mut expected = [0.0; ADPCM_BLOCK_SIZE];
+		channel.decode_block(1, &mut expected);
+		assert_eq!(channel.with_block(1, |samples| *samples), expected);
+		assert_eq!(channel.cache.lock().unwrap().index, Some(1));
+		// while the cache is in use, blocks are decoded
+		// separately and the cache is left alone
+		let cache = channel.cache.lock().unwrap();
+		let mut expected = [0.0; ADPCM_BLOCK_SIZE];
+		channel.decode_block(2, &mut expected);
+		assert_eq!(channel.with_block(2, |samples| *samples), expected);
+		assert_eq!(cache.index, Some(1));
+	}
+
+	/// Creates a slice where each frame's value is its index.
+	fn counting_slice(len: usize) -> SampleSlice {
+		SampleSlice::new(Samples::new(
+			(0..len).map(|i| Frame::from_mono(i as f32)).collect(),
+			SampleFormat::F32,
+			true,
+		))
+	}
+
+	fn values(frames: &[Frame]) -> Vec<f32> {
+		frames.iter().map(|frame| frame.left).collect()
+	}
+
+	#[test]
+	fn slices_are_clamped() {
+		let slice = counting_slice(100);
+		let inner = slice.slice(10, 1000);
+		assert_eq!(inner.len(), 90);
+		assert_eq!(inner.to_frames()[0].left, 10.0);
+		let nested = inner.slice(5, 15);
+		assert_eq!(nested.len(), 10);
+		assert_eq!(
+			values(&nested.to_frames()),
+			(15..25).map(|i| i as f32).collect::<Vec<_>>()
+		);
+		assert_eq!(slice.slice(50, 20).len(), 0);
+		assert_eq!(slice.slice(200, 300).len(), 0);
+	}
+
+	#[test]
+	fn fill_outside_of_slice_is_silent() {
+		let slice = counting_slice(100).slice(10, 20);
+		let mut out = [Frame::from_mono(-1.0); 4];
+		slice.fill(-2, &mut out);
+		assert_eq!(values(&out), [0.0, 0.0, 10.0, 11.0]);
+		slice.fill(8, &mut out);
+		assert_eq!(values(&out), [18.0, 19.0, 0.0, 0.0]);
+		slice.fill(-10, &mut out);
+		assert_eq!(values(&out), [0.0; 4]);
+		slice.fill(10, &mut out);
+		assert_eq!(values(&out), [0.0; 4]);
+		let mut out = [Frame::from_mono(-1.0); 14];
+		slice.fill(-2, &mut out);
+		assert_eq!(out[1].left, 0.0);
+		assert_eq!(out[2].left, 10.0);
+		assert_eq!(out[11].left, 19.0);
+		assert_eq!(out[12].left, 0.0);
+	}
+}
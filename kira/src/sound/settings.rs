@@ -1,6 +1,6 @@
-use crate::{group::GroupSet, mixer::TrackIndex};
+use crate::{group::GroupSet, instance::InstanceLimitBehavior, mixer::TrackIndex, Marker};
 
-use super::SoundId;
+use super::{ChannelPolicy, Normalization, SampleFormat, SoundId};
 
 /// Settings for a [`Sound`](crate::sound::Sound).
 #[derive(Debug, Clone)]
@@ -39,6 +39,37 @@ pub struct SoundSettings {
 	pub default_loop_start: Option<f64>,
 	/// The groups this sound belongs to.
 	pub groups: GroupSet,
+	/// Named points in time that instances of this sound
+	/// will emit events for when they're passed.
+	pub markers: Vec<Marker>,
+	/// How the sound's channels should be converted to stereo
+	/// when it's loaded from a file.
+	pub channel_policy: ChannelPolicy,
+	/// How the sound's samples should be stored in memory.
+	pub sample_format: SampleFormat,
+	/// Whether only one channel should be stored for sounds
+	/// whose left and right channels are identical, such as
+	/// sounds loaded from mono files.
+	///
+	/// This halves the memory used by mono sounds without
+	/// changing how they sound.
+	pub collapse_mono: bool,
+	/// A level the sound's volume should be adjusted to
+	/// when it's loaded.
+	pub normalize: Option<Normalization>,
+	/// Whether a summary of the sound's waveform at multiple
+	/// resolutions should be built when the sound is loaded.
+	///
+	/// This uses a small amount of extra memory, but makes
+	/// [`Sound::waveform`](crate::sound::Sound::waveform)
+	/// much faster for long time ranges.
+	pub waveform_overview: bool,
+	/// The maximum number of instances of this sound that
+	/// can play at the same time.
+	pub max_instances: Option<usize>,
+	/// What should happen when the sound is played while the
+	/// maximum number of instances are already playing.
+	pub instance_limit_behavior: InstanceLimitBehavior,
 }
 
 impl SoundSettings {
@@ -94,6 +125,108 @@ impl SoundSettings {
 			..self
 		}
 	}
+
+	/// Sets how the sound's channels should be converted to stereo
+	/// when it's loaded from a file.
+	pub fn channel_policy(self, channel_policy: ChannelPolicy) -> Self {
+		Self {
+			channel_policy,
+			..self
+		}
+	}
+
+	/// Sets how the sound's samples should be stored in memory.
+	pub fn sample_format(self, sample_format: SampleFormat) -> Self {
+		Self {
+			sample_format,
+			..self
+		}
+	}
+
+	/// Sets whether only one channel should be stored for sounds
+	/// whose left and right channels are identical.
+	pub fn collapse_mono(self, collapse_mono: bool) -> Self {
+		Self {
+			collapse_mono,
+			..self
+		}
+	}
+
+	/// Sets a level the sound's volume should be adjusted to
+	/// when it's loaded.
+	pub fn normalize(self, normalization: Normalization) -> Self {
+		Self {
+			normalize: Some(normalization),
+			..self
+		}
+	}
+
+	/// Sets whether a summary of the sound's waveform should
+	/// be built when the sound is loaded.
+	pub fn waveform_overview(self, waveform_overview: bool) -> Self {
+		Self {
+			waveform_overview,
+			..self
+		}
+	}
+
+	/// Sets the maximum number of instances of this sound that
+	/// can play at the same time.
+	pub fn max_instances(self, max_instances: usize) -> Self {
+		Self {
+			max_instances: Some(max_instances),
+			..self
+		}
+	}
+
+	/// Sets what should happen when the sound is played while the
+	/// maximum number of instances are already playing.
+	pub fn instance_limit_behavior(self, instance_limit_behavior: InstanceLimitBehavior) -> Self {
+		Self {
+			instance_limit_behavior,
+			..self
+		}
+	}
+
+	/// Adds a named marker at the given position (in seconds).
+	pub fn marker(mut self, position: f64, name: impl Into<String>) -> Self {
+		self.markers.push(Marker::new(position, name));
+		self
+	}
+}
+
+/// The settings that affect how a sound's audio is
+/// decoded and stored.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct LoadSettings {
+	pub channel_policy: ChannelPolicy,
+	pub sample_format: SampleFormat,
+	pub collapse_mono: bool,
+	pub normalize: Option<Normalization>,
+	pub waveform_overview: bool,
+}
+
+impl SoundSettings {
+	pub(crate) fn load_settings(&self) -> LoadSettings {
+		LoadSettings {
+			channel_policy: self.channel_policy,
+			sample_format: self.sample_format,
+			collapse_mono: self.collapse_mono,
+			normalize: self.normalize,
+			waveform_overview: self.waveform_overview,
+		}
+	}
+
+	pub(crate) fn with_load_settings(self, load_settings: LoadSettings) -> Self {
+		Self {
+			channel_policy: load_settings.channel_policy,
+			sample_format: load_settings.sample_format,
+			collapse_mono: load_settings.collapse_mono,
+			normalize: load_settings.normalize,
+			waveform_overview: load_settings.waveform_overview,
+			..self
+		}
+	}
 }
 
 impl Default for SoundSettings {
@@ -105,6 +238,14 @@ impl Default for SoundSettings {
 			semantic_duration: None,
 			default_loop_start: None,
 			groups: GroupSet::new(),
+			markers: vec![],
+			channel_policy: ChannelPolicy::default(),
+			sample_format: SampleFormat::default(),
+			collapse_mono: true,
+			normalize: None,
+			waveform_overview: false,
+			max_instances: None,
+			instance_limit_behavior: InstanceLimitBehavior::default(),
 		}
 	}
 }
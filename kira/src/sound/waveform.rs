@@ -0,0 +1,227 @@
+use crate::Frame;
+
+use super::samples::{SampleSlice, Samples};
+
+/// The number of frames summarized by each bucket in
+/// the most detailed level of a waveform overview.
+const OVERVIEW_BASE_BUCKET_SIZE: usize = 256;
+
+/// A summary of the audio in a short span of time, used
+/// for drawing waveforms.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WaveformBucket {
+	/// The lowest sample value of each channel.
+	pub min: Frame,
+	/// The highest sample value of each channel.
+	pub max: Frame,
+	/// The root mean square of each channel.
+	pub rms: Frame,
+}
+
+/// Statistics about a range of frames that can be
+/// combined with the statistics of adjacent ranges.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Summary {
+	min: Frame,
+	max: Frame,
+	sum_of_squares: (f64, f64),
+	num_frames: usize,
+}
+
+impl Summary {
+	pub fn new() -> Self {
+		Self {
+			min: Frame::from_mono(f32::INFINITY),
+			max: Frame::from_mono(f32::NEG_INFINITY),
+			sum_of_squares: (0.0, 0.0),
+			num_frames: 0,
+		}
+	}
+
+	pub fn add_frame(&mut self, frame: Frame) {
+		self.min = Frame::new(
+			self.min.left.min(frame.left),
+			self.min.right.min(frame.right),
+		);
+		self.max = Frame::new(
+			self.max.left.max(frame.left),
+			self.max.right.max(frame.right),
+		);
+		self.sum_of_squares.0 += (frame.left as f64).powi(2);
+		self.sum_of_squares.1 += (frame.right as f64).powi(2);
+		self.num_frames += 1;
+	}
+
+	pub fn merge(&mut self, other: &Self) {
+		self.min = Frame::new(
+			self.min.left.min(other.min.left),
+			self.min.right.min(other.min.right),
+		);
+		self.max = Frame::new(
+			self.max.left.max(other.max.left),
+			self.max.right.max(other.max.right),
+		);
+		self.sum_of_squares.0 += other.sum_of_squares.0;
+		self.sum_of_squares.1 += other.sum_of_squares.1;
+		self.num_frames += other.num_frames;
+	}
+
+	/// Converts the summary to a bucket. Empty summaries
+	/// are treated as silence.
+	pub fn bucket(&self) -> WaveformBucket {
+		if self.num_frames == 0 {
+			return WaveformBucket {
+				min: Frame::from_mono(0.0),
+				max: Frame::from_mono(0.0),
+				rms: Frame::from_mono(0.0),
+			};
+		}
+		WaveformBucket {
+			min: self.min,
+			max: self.max,
+			rms: Frame::new(
+				(self.sum_of_squares.0 / self.num_frames as f64).sqrt() as f32,
+				(self.sum_of_squares.1 / self.num_frames as f64).sqrt() as f32,
+			),
+		}
+	}
+}
+
+/// Summarizes the frames from `start` to `end` (exclusive)
+/// by reading each frame.
+pub(crate) fn summarize_frames(samples: &Samples, start: usize, end: usize) -> Summary {
+	let mut summary = Summary::new();
+	if start < end {
+		samples.for_each_frame(start, end, |_, frame| summary.add_frame(frame));
+	}
+	summary
+}
+
+/// A pyramid of summaries of sample data at increasingly
+/// coarse resolutions.
+///
+/// Each bucket in the first level summarizes a fixed number
+/// of frames, and each bucket in the following levels
+/// summarizes two buckets from the previous level, so any
+/// range of frames can be summarized by combining a small
+/// number of buckets.
+#[derive(Debug)]
+pub(crate) struct WaveformOverview {
+	levels: Vec<Vec<Summary>>,
+}
+
+impl WaveformOverview {
+	pub fn new(samples: &Samples) -> Self {
+		let mut level = vec![];
+		let mut start = 0;
+		while start < samples.len() {
+			let end = (start + OVERVIEW_BASE_BUCKET_SIZE).min(samples.len());
+			level.push(summarize_frames(samples, start, end));
+			start = end;
+		}
+		let mut levels = vec![level];
+		while levels.last().unwrap().len() > 1 {
+			let next_level = levels
+				.last()
+				.unwrap()
+				.chunks(2)
+				.map(|pair| {
+					let mut summary = pair[0];
+					if let Some(second) = pair.get(1) {
+						summary.merge(second);
+					}
+					summary
+				})
+				.collect();
+			levels.push(next_level);
+		}
+		Self { levels }
+	}
+
+	/// Summarizes the frames from `start` to `end` (exclusive).
+	pub fn summarize(&self, samples: &Samples, start: usize, end: usize) -> Summary {
+		let first_bucket = (start + OVERVIEW_BASE_BUCKET_SIZE - 1) / OVERVIEW_BASE_BUCKET_SIZE;
+		let last_bucket = end / OVERVIEW_BASE_BUCKET_SIZE;
+		if first_bucket >= last_bucket {
+			return summarize_frames(samples, start, end);
+		}
+		// frames that don't fill a whole bucket are summarized directly
+		let mut summary =
+			summarize_frames(samples, start, first_bucket * OVERVIEW_BASE_BUCKET_SIZE);
+		summary.merge(&summarize_frames(
+			samples,
+			last_bucket * OVERVIEW_BASE_BUCKET_SIZE,
+			end,
+		));
+		// climb the pyramid, taking the buckets at the edges
+		// of the range that don't pair up with a neighbor
+		let (mut low, mut high) = (first_bucket, last_bucket);
+		for (i, level) in self.levels.iter().enumerate() {
+			if low >= high {
+				break;
+			}
+			if i == self.levels.len() - 1 {
+				for bucket in &level[low..high] {
+					summary.merge(bucket);
+				}
+				break;
+			}
+			if low % 2 == 1 {
+				summary.merge(&level[low]);
+				low += 1;
+			}
+			if high % 2 == 1 {
+				high -= 1;
+				summary.merge(&level[high]);
+			}
+			low /= 2;
+			high /= 2;
+		}
+		summary
+	}
+}
+
+/// The sample data of a sound, which can be summarized
+/// in seconds.
+///
+/// This is shared with the sound's handles so waveforms can
+/// still be drawn after the sound is sent to the audio thread.
+#[derive(Debug, Clone)]
+pub(crate) struct WaveformSource {
+	sample_rate: u32,
+	samples: SampleSlice,
+}
+
+impl WaveformSource {
+	pub fn new(sample_rate: u32, samples: SampleSlice) -> Self {
+		Self {
+			sample_rate,
+			samples,
+		}
+	}
+
+	/// Summarizes the audio between `start` and `end` (in seconds).
+	///
+	/// If the range is shorter than a frame, the frame at
+	/// `start` is used so the summary is never empty
+	/// inside the sound.
+	pub fn summarize(&self, start: f64, end: f64) -> Summary {
+		let to_frame = |time: f64| (time.max(0.0) * self.sample_rate as f64) as usize;
+		let start_frame = to_frame(start);
+		let end_frame = to_frame(end).max(start_frame + 1);
+		self.samples.summarize(start_frame, end_frame)
+	}
+
+	/// Divides the time range from `start` to `end` (in seconds)
+	/// into `num_buckets` equal spans and summarizes each one.
+	pub fn waveform(&self, start: f64, end: f64, num_buckets: usize) -> Vec<WaveformBucket> {
+		let bucket_duration = (end - start) / num_buckets as f64;
+		(0..num_buckets)
+			.map(|i| {
+				let bucket_start = start + bucket_duration * i as f64;
+				self.summarize(bucket_start, bucket_start + bucket_duration)
+					.bucket()
+			})
+			.collect()
+	}
+}
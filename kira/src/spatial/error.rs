@@ -0,0 +1,43 @@
+//! Things that can go wrong with spatial audio.
+
+use thiserror::Error;
+
+#[cfg(feature = "wav")]
+use crate::sound::error::SoundFromFileError;
+
+/// Something that can go wrong when creating an
+/// [`HrirSet`](super::HrirSet).
+#[derive(Debug, Error)]
+pub enum HrirSetError {
+	/// The set doesn't have any measurements.
+	#[error("HRIR sets must have at least one measurement")]
+	NoMeasurements,
+
+	/// The sample rate is zero.
+	#[error("The sample rate of an HRIR set must be greater than zero")]
+	InvalidSampleRate,
+
+	/// Not every impulse response file has the same sample rate.
+	#[error("Every impulse response in an HRIR set must have the same sample rate")]
+	InconsistentSampleRate,
+
+	/// Not every impulse response in the set has the same length.
+	#[error("Every impulse response in an HRIR set must have the same length")]
+	InconsistentLength,
+
+	/// The impulse responses are longer than Kira supports.
+	#[error("Impulse responses cannot be longer than {max_duration} seconds")]
+	TooLong {
+		/// The maximum duration of an impulse response (in seconds).
+		max_duration: f64,
+	},
+
+	/// An error occurred when interacting with the filesystem.
+	#[error("{0}")]
+	IoError(#[from] std::io::Error),
+
+	/// An error occurred when reading an impulse response file.
+	#[cfg(feature = "wav")]
+	#[error("{0}")]
+	SoundFromFileError(#[from] SoundFromFileError),
+}
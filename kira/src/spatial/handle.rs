@@ -0,0 +1,131 @@
+//! Interfaces for controlling the listener and emitters.
+
+use flume::Sender;
+use thiserror::Error;
+
+use crate::command::{Command, SpatialCommand};
+
+use super::{EmitterId, HrirSet, Vector3};
+
+/// Something that can go wrong when using an [`EmitterHandle`]
+/// to control an emitter.
+#[derive(Debug, Error)]
+pub enum EmitterHandleError {
+	/// The audio thread has finished and can no longer receive commands.
+	#[error("The backend cannot receive commands because it no longer exists")]
+	BackendDisconnected,
+}
+
+#[derive(Debug, Clone)]
+/// Allows you to control an emitter.
+pub struct EmitterHandle {
+	id: EmitterId,
+	command_sender: Sender<Command>,
+}
+
+impl EmitterHandle {
+	pub(crate) fn new(id: EmitterId, command_sender: Sender<Command>) -> Self {
+		Self { id, command_sender }
+	}
+
+	/// Returns the ID of the emitter.
+	pub fn id(&self) -> EmitterId {
+		self.id
+	}
+
+	/// Sets the position of the emitter.
+	pub fn set_position(&mut self, position: impl Into<Vector3>) -> Result<(), EmitterHandleError> {
+		self.command_sender
+			.send(SpatialCommand::SetEmitterPosition(self.id, position.into()).into())
+			.map_err(|_| EmitterHandleError::BackendDisconnected)
+	}
+
+	/// Sets the velocity (in units per second) of the emitter
+	/// used for the Doppler effect.
+	pub fn set_velocity(&mut self, velocity: impl Into<Vector3>) -> Result<(), EmitterHandleError> {
+		self.command_sender
+			.send(SpatialCommand::SetEmitterVelocity(self.id, Some(velocity.into())).into())
+			.map_err(|_| EmitterHandleError::BackendDisconnected)
+	}
+
+	/// Goes back to deriving the velocity of the emitter
+	/// from changes in its position.
+	pub fn clear_velocity(&mut self) -> Result<(), EmitterHandleError> {
+		self.command_sender
+			.send(SpatialCommand::SetEmitterVelocity(self.id, None).into())
+			.map_err(|_| EmitterHandleError::BackendDisconnected)
+	}
+}
+
+/// Something that can go wrong when using a [`ListenerHandle`]
+/// to control the listener.
+#[derive(Debug, Error)]
+pub enum ListenerHandleError {
+	/// The audio thread has finished and can no longer receive commands.
+	#[error("The backend cannot receive commands because it no longer exists")]
+	BackendDisconnected,
+}
+
+#[derive(Debug, Clone)]
+/// Allows you to control the listener.
+pub struct ListenerHandle {
+	command_sender: Sender<Command>,
+}
+
+impl ListenerHandle {
+	pub(crate) fn new(command_sender: Sender<Command>) -> Self {
+		Self { command_sender }
+	}
+
+	/// Sets the position of the listener.
+	pub fn set_position(
+		&mut self,
+		position: impl Into<Vector3>,
+	) -> Result<(), ListenerHandleError> {
+		self.command_sender
+			.send(SpatialCommand::SetListenerPosition(position.into()).into())
+			.map_err(|_| ListenerHandleError::BackendDisconnected)
+	}
+
+	/// Sets the velocity (in units per second) of the listener
+	/// used for the Doppler effect.
+	pub fn set_velocity(
+		&mut self,
+		velocity: impl Into<Vector3>,
+	) -> Result<(), ListenerHandleError> {
+		self.command_sender
+			.send(SpatialCommand::SetListenerVelocity(Some(velocity.into())).into())
+			.map_err(|_| ListenerHandleError::BackendDisconnected)
+	}
+
+	/// Goes back to deriving the velocity of the listener
+	/// from changes in its position.
+	pub fn clear_velocity(&mut self) -> Result<(), ListenerHandleError> {
+		self.command_sender
+			.send(SpatialCommand::SetListenerVelocity(None).into())
+			.map_err(|_| ListenerHandleError::BackendDisconnected)
+	}
+
+	/// Sets the HRIRs used to render binaural emitters, or
+	/// goes back to panning them if `None`.
+	pub fn set_hrir_set(
+		&mut self,
+		hrir_set: impl Into<Option<HrirSet>>,
+	) -> Result<(), ListenerHandleError> {
+		self.command_sender
+			.send(SpatialCommand::SetHrirSet(hrir_set.into()).into())
+			.map_err(|_| ListenerHandleError::BackendDisconnected)
+	}
+
+	/// Sets the direction the listener is facing and
+	/// the direction of the top of the listener's head.
+	pub fn set_orientation(
+		&mut self,
+		forward: impl Into<Vector3>,
+		up: impl Into<Vector3>,
+	) -> Result<(), ListenerHandleError> {
+		self.command_sender
+			.send(SpatialCommand::SetListenerOrientation(forward.into(), up.into()).into())
+			.map_err(|_| ListenerHandleError::BackendDisconnected)
+	}
+}
@@ -0,0 +1,504 @@
+use std::f64::consts::PI;
+#[cfg(feature = "wav")]
+use std::path::Path;
+
+#[cfg(feature = "wav")]
+use crate::sound::{decode_wav, error::SoundFromFileError};
+use crate::Frame;
+
+use super::{error::HrirSetError, Vector3};
+
+/// The longest an impulse response in an [`HrirSet`] can be (in seconds).
+pub const MAX_HRIR_DURATION: f64 = 0.0125;
+/// The number of input samples binaural instances remember,
+/// which covers the longest impulse response at output sample
+/// rates up to 192 kHz.
+const HISTORY_LENGTH: usize = 4096;
+/// The number of measured directions blended together to get
+/// the impulse response for a direction between them.
+const NUM_BLENDED_MEASUREMENTS: usize = 3;
+
+/// The radius (in meters) of the head used by [`HrirSet::spherical_head`].
+const HEAD_RADIUS: f64 = 0.0875;
+/// The speed of sound (in meters per second) used by
+/// [`HrirSet::spherical_head`].
+const SPEED_OF_SOUND: f64 = 343.0;
+/// How much high frequencies are attenuated for sounds
+/// on the opposite side of the head from an ear.
+const MIN_HEAD_SHADOW: f64 = 0.1;
+/// The angle (in radians) between a sound and an ear at which
+/// high frequencies are attenuated the most.
+const MAX_HEAD_SHADOW_ANGLE: f64 = PI * 5.0 / 6.0;
+/// The length (in seconds) of the impulse responses
+/// generated by [`HrirSet::spherical_head`].
+const SPHERICAL_HEAD_HRIR_DURATION: f64 = 0.003;
+
+/// The weights of the measurements blended together
+/// for a direction.
+pub(crate) type HrirWeights = [(usize, f32); NUM_BLENDED_MEASUREMENTS];
+
+/// Returns the direction of a measurement relative to the
+/// listener, where x is to the right, y is up, and z is forward.
+fn measurement_direction(azimuth: f64, elevation: f64) -> Vector3 {
+	let azimuth = azimuth.to_radians();
+	let elevation = elevation.to_radians();
+	Vector3::new(
+		elevation.cos() * azimuth.sin(),
+		elevation.sin(),
+		elevation.cos() * azimuth.cos(),
+	)
+}
+
+/// Gets the elevation and azimuth (in degrees) from the name
+/// of a file in the MIT KEMAR measurements, like `H-10e050a.wav`.
+#[cfg(feature = "wav")]
+fn parse_mit_kemar_file_name(name: &str) -> Option<(f64, f64)> {
+	let name = name.strip_prefix('H')?.strip_suffix("a.wav")?;
+	let (elevation, azimuth) = name.split_at(name.find('e')?);
+	Some((elevation.parse().ok()?, azimuth[1..].parse().ok()?))
+}
+
+/// A pair of impulse responses measured at the entrance
+/// of each ear for a sound coming from one direction.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct HrirMeasurement {
+	/// The horizontal angle of the sound (in degrees), where
+	/// 0 is in front of the listener and 90 is to the right.
+	pub azimuth: f64,
+	/// The vertical angle of the sound (in degrees), where
+	/// 0 is level with the listener's ears and 90 is above.
+	pub elevation: f64,
+	/// The impulse response for the left ear.
+	pub left: Vec<f32>,
+	/// The impulse response for the right ear.
+	pub right: Vec<f32>,
+}
+
+/// A set of head-related impulse responses (HRIRs), which
+/// describe how sounds coming from different directions
+/// reach each ear.
+///
+/// Kira doesn't bundle any measured HRIRs and can't read SOFA
+/// files, which would require an HDF5 decoder. Sets measured from
+/// real heads can be converted from SOFA files or other datasets
+/// into [`HrirMeasurement`]s ahead of time. With the
+/// `serde_support` feature, measurements can also be
+/// deserialized from data files, and with the `wav` feature,
+/// the freely available MIT KEMAR measurements can be loaded
+/// with [`HrirSet::from_mit_kemar`]. Otherwise,
+/// [`HrirSet::spherical_head`] generates a set from a simple
+/// model of a head.
+#[derive(Debug, Clone)]
+pub struct HrirSet {
+	sample_rate: u32,
+	length: usize,
+	directions: Vec<Vector3>,
+	left: Vec<f32>,
+	right: Vec<f32>,
+}
+
+impl HrirSet {
+	/// Creates a new HRIR set from measurements recorded at
+	/// the given sample rate.
+	///
+	/// Every impulse response must have the same length.
+	pub fn new(sample_rate: u32, measurements: Vec<HrirMeasurement>) -> Result<Self, HrirSetError> {
+		if sample_rate == 0 {
+			return Err(HrirSetError::InvalidSampleRate);
+		}
+		let length = match measurements.first() {
+			Some(measurement) => measurement.left.len(),
+			None => return Err(HrirSetError::NoMeasurements),
+		};
+		if measurements.iter().any(|measurement| {
+			measurement.left.len() != length || measurement.right.len() != length
+		}) {
+			return Err(HrirSetError::InconsistentLength);
+		}
+		if length as f64 / sample_rate as f64 > MAX_HRIR_DURATION {
+			return Err(HrirSetError::TooLong {
+				max_duration: MAX_HRIR_DURATION,
+			});
+		}
+		let mut directions = Vec::with_capacity(measurements.len());
+		let mut left = Vec::with_capacity(measurements.len() * length);
+		let mut right = Vec::with_capacity(measurements.len() * length);
+		for measurement in measurements {
+			directions.push(measurement_direction(
+				measurement.azimuth,
+				measurement.elevation,
+			));
+			left.extend(measurement.left);
+			right.extend(measurement.right);
+		}
+		Ok(Self {
+			sample_rate,
+			length,
+			directions,
+			left,
+			right,
+		})
+	}
+
+	/// Creates an HRIR set from a model of a spherical head.
+	///
+	/// The model delays and muffles sounds for the ear facing
+	/// away from them, but it doesn't distinguish between
+	/// sounds in front of and behind the listener or give
+	/// cues for elevation. Use measured HRIRs for those.
+	pub fn spherical_head(sample_rate: u32) -> Result<Self, HrirSetError> {
+		let length = (SPHERICAL_HEAD_HRIR_DURATION * sample_rate as f64).ceil() as usize;
+		let mut measurements = vec![];
+		for elevation in (-40..=80).step_by(20) {
+			for azimuth in (0..360).step_by(10) {
+				measurements.push(Self::spherical_head_measurement(
+					azimuth as f64,
+					elevation as f64,
+					sample_rate,
+					length,
+				));
+			}
+		}
+		measurements.push(Self::spherical_head_measurement(
+			0.0,
+			90.0,
+			sample_rate,
+			length,
+		));
+		Self::new(sample_rate, measurements)
+	}
+
+	/// Loads the "compact" set of KEMAR dummy head measurements
+	/// published by the MIT Media Lab (Gardner and Martin, 1994).
+	///
+	/// `path` is the folder containing the `elev` folders, each of
+	/// which contains stereo files named like `H-10e050a.wav` for an
+	/// elevation of -10 degrees and an azimuth of 50 degrees. The
+	/// compact set only has measurements for sounds on the right,
+	/// so they're mirrored to get measurements for sounds on the left.
+	#[cfg(feature = "wav")]
+	pub fn from_mit_kemar(path: impl AsRef<Path>) -> Result<Self, HrirSetError> {
+		let mut sample_rate = None;
+		let mut measurements = vec![];
+		for folder in std::fs::read_dir(path)? {
+			let folder = folder?.path();
+			if !folder.is_dir() {
+				continue;
+			}
+			for file in std::fs::read_dir(folder)? {
+				let file = file?.path();
+				let (elevation, azimuth) = match file
+					.file_name()
+					.and_then(|name| name.to_str())
+					.and_then(parse_mit_kemar_file_name)
+				{
+					Some(direction) => direction,
+					None => continue,
+				};
+				let mut left = vec![];
+				let mut right = vec![];
+				let file_sample_rate = decode_wav(&file, |samples| match samples {
+					[left_sample, right_sample] => {
+						left.push(*left_sample);
+						right.push(*right_sample);
+						Ok(())
+					}
+					_ => Err(SoundFromFileError::UnsupportedChannelConfiguration),
+				})?;
+				if *sample_rate.get_or_insert(file_sample_rate) != file_sample_rate {
+					return Err(HrirSetError::InconsistentSampleRate);
+				}
+				if azimuth > 0.0 && azimuth < 180.0 {
+					measurements.push(HrirMeasurement {
+						azimuth: -azimuth,
+						elevation,
+						left: right.clone(),
+						right: left.clone(),
+					});
+				}
+				measurements.push(HrirMeasurement {
+					azimuth,
+					elevation,
+					left,
+					right,
+				});
+			}
+		}
+		match sample_rate {
+			Some(sample_rate) => Self::new(sample_rate, measurements),
+			None => Err(HrirSetError::NoMeasurements),
+		}
+	}
+
+	fn spherical_head_measurement(
+		azimuth: f64,
+		elevation: f64,
+		sample_rate: u32,
+		length: usize,
+	) -> HrirMeasurement {
+		let direction = measurement_direction(azimuth, elevation);
+		HrirMeasurement {
+			azimuth,
+			elevation,
+			left: Self::spherical_head_ear_response(
+				direction,
+				Vector3::new(-1.0, 0.0, 0.0),
+				sample_rate,
+				length,
+			),
+			right: Self::spherical_head_ear_response(
+				direction,
+				Vector3::new(1.0, 0.0, 0.0),
+				sample_rate,
+				length,
+			),
+		}
+	}
+
+	/// Returns the impulse response of one ear using the spherical
+	/// head model from Brown and Duda's "A Structural Model for
+	/// Binaural Sound Synthesis".
+	fn spherical_head_ear_response(
+		direction: Vector3,
+		ear: Vector3,
+		sample_rate: u32,
+		length: usize,
+	) -> Vec<f32> {
+		let sample_rate = sample_rate as f64;
+		// the angle between the sound and the ear (0 = facing the ear)
+		let angle = direction.dot(ear).clamp(-1.0, 1.0).acos();
+		// sound takes longer to reach the far side of the head
+		let delay = HEAD_RADIUS / SPEED_OF_SOUND
+			* if angle < PI / 2.0 {
+				1.0 - angle.cos()
+			} else {
+				1.0 + angle - PI / 2.0
+			};
+		// the head shadow is a one-pole, one-zero high shelf
+		// filter, discretized with the bilinear transform
+		let shadow = (1.0 + MIN_HEAD_SHADOW / 2.0)
+			+ (1.0 - MIN_HEAD_SHADOW / 2.0) * (angle / MAX_HEAD_SHADOW_ANGLE * PI).cos();
+		let k = sample_rate * HEAD_RADIUS / SPEED_OF_SOUND;
+		let b0 = (1.0 + shadow * k) / (1.0 + k);
+		let b1 = (1.0 - shadow * k) / (1.0 + k);
+		let a1 = (1.0 - k) / (1.0 + k);
+		let delay_samples = delay * sample_rate;
+		let delay_whole = delay_samples as usize;
+		let delay_fraction = delay_samples - delay_whole as f64;
+		let mut response = Vec::with_capacity(length);
+		let mut previous_input = 0.0;
+		let mut previous_output = 0.0;
+		for i in 0..length {
+			// a fractionally delayed impulse
+			let input = if i == delay_whole {
+				1.0 - delay_fraction
+			} else if i == delay_whole + 1 {
+				delay_fraction
+			} else {
+				0.0
+			};
+			let output = b0 * input + b1 * previous_input - a1 * previous_output;
+			response.push(output as f32);
+			previous_input = input;
+			previous_output = output;
+		}
+		response
+	}
+
+	/// Returns the sample rate the impulse responses were recorded at.
+	pub fn sample_rate(&self) -> u32 {
+		self.sample_rate
+	}
+
+	/// Returns the number of measured directions.
+	pub fn num_measurements(&self) -> usize {
+		self.directions.len()
+	}
+
+	/// Returns the measurements closest to a direction (relative
+	/// to the listener) and how much to blend each one in.
+	///
+	/// Each measurement's weight is how much closer it is than the
+	/// next closest measurement that isn't blended in, so the weights
+	/// change smoothly as the direction moves between measurements.
+	pub(crate) fn weights(&self, direction: Vector3) -> HrirWeights {
+		let direction = direction.normalized();
+		// the closest measurements, plus one more that
+		// sets how far away a measurement can be to be used
+		let mut closest = [(0, f64::INFINITY); NUM_BLENDED_MEASUREMENTS + 1];
+		for (index, measurement_direction) in self.directions.iter().enumerate() {
+			let distance = 1.0 - direction.dot(*measurement_direction);
+			if distance < closest[NUM_BLENDED_MEASUREMENTS].1 {
+				let mut i = NUM_BLENDED_MEASUREMENTS;
+				while i > 0 && distance < closest[i - 1].1 {
+					closest[i] = closest[i - 1];
+					i -= 1;
+				}
+				closest[i] = (index, distance);
+			}
+		}
+		// 2 is the farthest apart two directions can be
+		let cutoff = closest[NUM_BLENDED_MEASUREMENTS].1.min(2.0 + f64::EPSILON);
+		let mut weights = [(0, 0.0); NUM_BLENDED_MEASUREMENTS];
+		let mut total_weight = 0.0;
+		for (weight, (index, distance)) in weights.iter_mut().zip(closest.iter()) {
+			if distance.is_finite() {
+				*weight = (*index, (cutoff - distance).max(0.0));
+				total_weight += weight.1;
+			}
+		}
+		if total_weight > 0.0 {
+			for weight in &mut weights {
+				weight.1 /= total_weight;
+			}
+		} else {
+			// every measurement is the same distance away
+			weights[0] = (closest[0].0, 1.0);
+		}
+		let mut out = [(0, 0.0); NUM_BLENDED_MEASUREMENTS];
+		for (out, (index, weight)) in out.iter_mut().zip(weights.iter()) {
+			*out = (*index, *weight as f32);
+		}
+		out
+	}
+}
+
+/// Renders audio for headphones by convolving it
+/// with the impulse responses from an [`HrirSet`].
+#[derive(Debug, Clone)]
+pub(crate) struct BinauralRenderer {
+	history: Vec<f32>,
+	write_index: usize,
+}
+
+impl BinauralRenderer {
+	pub fn new() -> Self {
+		Self {
+			history: vec![0.0; HISTORY_LENGTH],
+			write_index: 0,
+		}
+	}
+
+	/// Clears the remembered input so the renderer
+	/// can be reused for another instance.
+	pub fn reset(&mut self) {
+		for sample in &mut self.history {
+			*sample = 0.0;
+		}
+		self.write_index = 0;
+	}
+
+	pub fn process(
+		&mut self,
+		dt: f64,
+		input: Frame,
+		hrir_set: &HrirSet,
+		weights: HrirWeights,
+	) -> Frame {
+		self.write_index = (self.write_index + 1) % HISTORY_LENGTH;
+		self.history[self.write_index] = (input.left + input.right) / 2.0;
+		// the audio may not have the same sample rate as the
+		// impulse responses, so the input is read between samples
+		let samples_per_tap = 1.0 / (dt * hrir_set.sample_rate as f64);
+		let mut out = Frame::from_mono(0.0);
+		for tap in 0..hrir_set.length {
+			let delay = tap as f64 * samples_per_tap;
+			let delay_whole = delay as usize;
+			if delay_whole + 1 >= HISTORY_LENGTH {
+				break;
+			}
+			let delay_fraction = (delay - delay_whole as f64) as f32;
+			let current =
+				self.history[(self.write_index + HISTORY_LENGTH - delay_whole) % HISTORY_LENGTH];
+			let previous = self.history
+				[(self.write_index + HISTORY_LENGTH - delay_whole - 1) % HISTORY_LENGTH];
+			let sample = current + (previous - current) * delay_fraction;
+			for &(index, weight) in &weights {
+				if weight > 0.0 {
+					let offset = index * hrir_set.length + tap;
+					out.left += sample * hrir_set.left[offset] * weight;
+					out.right += sample * hrir_set.right[offset] * weight;
+				}
+			}
+		}
+		out
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn spherical_head_rejects_zero_sample_rate() {
+		assert!(matches!(
+			HrirSet::spherical_head(0),
+			Err(HrirSetError::InvalidSampleRate)
+		));
+	}
+
+	#[test]
+	fn spherical_head_is_louder_in_the_near_ear() {
+		let hrir_set = HrirSet::spherical_head(48000).unwrap();
+		let mut renderer = BinauralRenderer::new();
+		let weights = hrir_set.weights(Vector3::new(1.0, 0.0, 0.0));
+		let mut left = 0.0;
+		let mut right = 0.0;
+		for i in 0..480 {
+			let input = Frame::from_mono(if i % 2 == 0 { 1.0 } else { -1.0 });
+			let out = renderer.process(1.0 / 48000.0, input, &hrir_set, weights);
+			left += out.left.abs();
+			right += out.right.abs();
+		}
+		assert!(right > left * 2.0, "{} {}", left, right);
+	}
+
+	#[cfg(feature = "wav")]
+	#[test]
+	fn parses_mit_kemar_file_names() {
+		assert_eq!(parse_mit_kemar_file_name("H0e090a.wav"), Some((0.0, 90.0)));
+		assert_eq!(
+			parse_mit_kemar_file_name("H-10e355a.wav"),
+			Some((-10.0, 355.0))
+		);
+		assert_eq!(parse_mit_kemar_file_name("readme.txt"), None);
+	}
+
+	#[cfg(feature = "wav")]
+	#[test]
+	fn loads_and_mirrors_mit_kemar_measurements() {
+		let directory =
+			std::env::temp_dir().join(format!("kira-kemar-test-{}", std::process::id()));
+		let elevation_directory = directory.join("elev0");
+		std::fs::create_dir_all(&elevation_directory).unwrap();
+		for azimuth in &[0, 90, 180] {
+			let mut writer = hound::WavWriter::create(
+				elevation_directory.join(format!("H0e{:03}a.wav", azimuth)),
+				hound::WavSpec {
+					channels: 2,
+					sample_rate: 44100,
+					bits_per_sample: 16,
+					sample_format: hound::SampleFormat::Int,
+				},
+			)
+			.unwrap();
+			for _ in 0..128 {
+				writer.write_sample(i16::MAX / 4).unwrap();
+				writer.write_sample(i16::MAX / 2).unwrap();
+			}
+			writer.finalize().unwrap();
+		}
+		let hrir_set = HrirSet::from_mit_kemar(&directory).unwrap();
+		std::fs::remove_dir_all(&directory).ok();
+		assert_eq!(hrir_set.sample_rate(), 44100);
+		// 90 degrees is mirrored to -90 degrees
+		assert_eq!(hrir_set.num_measurements(), 4);
+		let (index, _) = hrir_set.weights(Vector3::new(-1.0, 0.0, 0.0))[0];
+		let offset = index * hrir_set.length;
+		assert!(hrir_set.left[offset] > hrir_set.right[offset]);
+	}
+}
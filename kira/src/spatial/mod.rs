@@ -0,0 +1,402 @@
+//! Positions sounds in 3D space.
+//!
+//! The audio manager has a single listener, which represents
+//! the position and orientation of the player's ears. Emitters
+//! are points in space that instances can be attached to using
+//! [`InstanceSettings::emitter`](crate::instance::InstanceSettings::emitter).
+//! The volume and panning of instances attached to an emitter
+//! are set automatically based on where the emitter is relative
+//! to the listener. Since Kira's output is stereo, "panning"
+//! here means panning between the left and right channels;
+//! there are no per-speaker gains for surround setups.
+//!
+//! Positions are sent to the audio thread as commands, so
+//! updating them every frame is cheap. Changes in volume and
+//! panning are smoothed so they don't cause clicks.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use kira::{
+//! # 	manager::AudioManager,
+//! # 	instance::InstanceSettings,
+//! # 	sound::Sound,
+//! # 	spatial::EmitterSettings,
+//! # };
+//! #
+//! # let mut audio_manager = AudioManager::new(Default::default())?;
+//! # let mut sound = audio_manager.add_sound(Sound::from_file("engine.ogg", Default::default())?)?;
+//! let mut listener = audio_manager.listener();
+//! let mut emitter = audio_manager.add_emitter(
+//! 	EmitterSettings::new().position([10.0, 0.0, 0.0]).distance_low_pass(2000.0),
+//! )?;
+//! sound.play(InstanceSettings::new().emitter(&emitter))?;
+//! // later, when the player or the car moves
+//! listener.set_position([0.0, 0.0, 5.0])?;
+//! listener.set_orientation([0.0, 0.0, -1.0], [0.0, 1.0, 0.0])?;
+//! emitter.set_position([8.0, 0.0, 0.0])?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! ## Doppler effect
+//!
+//! Emitters with a [`doppler_factor`](EmitterSettings::doppler_factor)
+//! above zero change the pitch of their instances when the emitter and
+//! listener move toward or away from each other. Velocities are derived
+//! from how far the listener and emitters move between position updates,
+//! but they can also be set explicitly with
+//! [`EmitterHandle::set_velocity`] and
+//! [`ListenerHandle::set_velocity`](handle::ListenerHandle::set_velocity),
+//! which is more accurate and avoids pitch jumps when objects teleport.
+//!
+//! ## Binaural rendering
+//!
+//! For players using headphones, emitters created with
+//! [`EmitterSettings::binaural`] can be rendered using
+//! head-related impulse responses (HRIRs) instead of panning,
+//! which gives a sense of whether sounds are in front of,
+//! behind, above, or below the listener. The HRIRs are set
+//! on the listener with
+//! [`ListenerHandle::set_hrir_set`](handle::ListenerHandle::set_hrir_set).
+//! Until an [`HrirSet`] is set, binaural emitters are panned like
+//! other emitters. No measured HRIRs are bundled and SOFA files
+//! aren't supported; see [`HrirSet`] for the available sources.
+//! Up to
+//! [`num_binaural_instances`](crate::manager::AudioManagerSettings::num_binaural_instances)
+//! instances can be rendered binaurally at a time, and any more
+//! are panned as well.
+//!
+//! ```no_run
+//! # use kira::{manager::AudioManager, spatial::{EmitterSettings, HrirSet}};
+//! #
+//! # let mut audio_manager = AudioManager::new(Default::default())?;
+//! audio_manager.listener().set_hrir_set(HrirSet::spherical_head(48000)?)?;
+//! let emitter = audio_manager.add_emitter(EmitterSettings::new().binaural())?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Kira uses a right-handed coordinate system: by default, the
+//! listener faces toward negative z, with positive y as up and
+//! positive x to the right.
+
+pub mod error;
+pub mod handle;
+mod hrtf;
+mod motion;
+mod scene;
+mod settings;
+mod vector;
+
+use uuid::Uuid;
+
+use crate::{util::generate_uuid, Frame};
+use handle::EmitterHandle;
+pub(crate) use hrtf::{BinauralRenderer, HrirWeights};
+pub use hrtf::{HrirMeasurement, HrirSet, MAX_HRIR_DURATION};
+use motion::Motion;
+pub(crate) use scene::SpatialScene;
+pub use settings::*;
+pub use vector::Vector3;
+
+/// The cutoff frequency (in Hz) of the distance low-pass
+/// filter when an emitter is at its minimum distance.
+const MAX_DISTANCE_LOW_PASS_CUTOFF: f64 = 20000.0;
+/// How close (in Hz) the smoothed cutoff of the distance
+/// low-pass filter has to get to its target before it
+/// jumps to it.
+const CUTOFF_SNAP_DISTANCE: f64 = 1.0;
+/// How long (in seconds) it takes the volume, panning, and
+/// filter cutoff of an emitter to mostly catch up to a
+/// change in position.
+const SMOOTHING_TIME: f64 = 0.02;
+/// The fastest the listener or an emitter can move toward or
+/// away from the other, as a fraction of the speed of sound,
+/// when calculating the Doppler effect.
+const MAX_DOPPLER_SPEED: f64 = 0.9;
+/// How far (as the cosine of an angle) the direction of a
+/// binaural emitter has to move before the HRIRs used for
+/// it are updated.
+const HRIR_UPDATE_THRESHOLD: f64 = 0.99996;
+
+/// A unique identifier for an emitter.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(transparent)
+)]
+pub struct EmitterId {
+	uuid: Uuid,
+}
+
+impl EmitterId {
+	pub(crate) fn new() -> Self {
+		Self {
+			uuid: generate_uuid(),
+		}
+	}
+}
+
+impl From<&EmitterHandle> for EmitterId {
+	fn from(handle: &EmitterHandle) -> Self {
+		handle.id()
+	}
+}
+
+/// The position and orientation of the player's ears.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Listener {
+	motion: Motion,
+	forward: Vector3,
+	up: Vector3,
+}
+
+impl Listener {
+	pub fn new() -> Self {
+		Self {
+			motion: Motion::new(Vector3::default()),
+			forward: Vector3::new(0.0, 0.0, -1.0),
+			up: Vector3::new(0.0, 1.0, 0.0),
+		}
+	}
+
+	pub fn set_position(&mut self, position: Vector3) {
+		self.motion.set_position(position);
+	}
+
+	pub fn set_velocity(&mut self, velocity: Option<Vector3>) {
+		self.motion.set_velocity(velocity);
+	}
+
+	pub fn set_orientation(&mut self, forward: Vector3, up: Vector3) {
+		self.forward = forward;
+		self.up = up;
+	}
+
+	fn right(&self) -> Vector3 {
+		self.forward.cross(self.up).normalized()
+	}
+
+	/// Returns the direction of a position relative to the listener,
+	/// where x is to the right, y is up, and z is forward.
+	fn local_direction(&self, position: Vector3) -> Vector3 {
+		let direction = (position - self.motion.position()).normalized();
+		let forward = self.forward.normalized();
+		let right = self.right();
+		let up = right.cross(forward);
+		Vector3::new(
+			direction.dot(right),
+			direction.dot(up),
+			direction.dot(forward),
+		)
+	}
+
+	pub fn update(&mut self, dt: f64) {
+		self.motion.update(dt);
+	}
+}
+
+/// How an emitter affects the instances attached to it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct SpatialOutput {
+	pub volume: f64,
+	pub panning: f64,
+	pub cutoff: Option<f64>,
+	/// The factor the pitch of instances is multiplied by
+	/// to simulate the Doppler effect.
+	pub doppler: f64,
+	/// The HRIRs to render instances with, if the emitter
+	/// is binaural and an HRIR set is loaded.
+	pub hrir_weights: Option<HrirWeights>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Emitter {
+	settings: EmitterSettings,
+	motion: Motion,
+	output: Option<SpatialOutput>,
+	/// The smoothed direction of the emitter relative to the listener.
+	hrir_direction: Option<Vector3>,
+	/// The direction the HRIR weights were last calculated
+	/// for, and the weights.
+	hrir_weights: Option<(Vector3, HrirWeights)>,
+}
+
+impl Emitter {
+	pub fn new(settings: EmitterSettings) -> Self {
+		Self {
+			settings,
+			motion: Motion::new(settings.position),
+			output: None,
+			hrir_direction: None,
+			hrir_weights: None,
+		}
+	}
+
+	pub fn set_position(&mut self, position: Vector3) {
+		self.motion.set_position(position);
+	}
+
+	pub fn set_velocity(&mut self, velocity: Option<Vector3>) {
+		self.motion.set_velocity(velocity);
+	}
+
+	pub fn output(&self) -> Option<SpatialOutput> {
+		self.output
+	}
+
+	fn target_output(&self, listener: &Listener) -> SpatialOutput {
+		let min_distance = self.settings.min_distance;
+		let max_distance = self.settings.max_distance.max(min_distance);
+		let offset = self.motion.position() - listener.motion.position();
+		let distance = offset.length();
+		let volume = self
+			.settings
+			.attenuation_curve
+			.volume(distance, min_distance, max_distance);
+		// sounds right on top of the listener shouldn't jump
+		// from one side to the other
+		let pan_amount = if min_distance > 0.0 {
+			(distance / min_distance).min(1.0)
+		} else {
+			1.0
+		};
+		let panning = 0.5 + 0.5 * offset.normalized().dot(listener.right()) * pan_amount;
+		let cutoff = self.settings.distance_low_pass.map(|far_cutoff| {
+			let fraction = if max_distance > min_distance {
+				((distance - min_distance) / (max_distance - min_distance)).clamp(0.0, 1.0)
+			} else {
+				0.0
+			};
+			// interpolate logarithmically, since that's how
+			// pitch is perceived
+			MAX_DISTANCE_LOW_PASS_CUTOFF
+				* (far_cutoff / MAX_DISTANCE_LOW_PASS_CUTOFF).powf(fraction)
+		});
+		SpatialOutput {
+			volume,
+			panning,
+			cutoff,
+			doppler: self.doppler(listener),
+			hrir_weights: None,
+		}
+	}
+
+	fn doppler(&self, listener: &Listener) -> f64 {
+		let speed_of_sound = self.settings.speed_of_sound;
+		let doppler_factor = self.settings.doppler_factor;
+		if doppler_factor <= 0.0 || speed_of_sound <= 0.0 {
+			return 1.0;
+		}
+		let direction = (listener.motion.position() - self.motion.position()).normalized();
+		let max_speed = MAX_DOPPLER_SPEED * speed_of_sound / doppler_factor;
+		// how fast the listener is moving away from the emitter
+		// and how fast the emitter is moving toward the listener
+		let listener_speed = listener
+			.motion
+			.velocity()
+			.dot(direction)
+			.clamp(-max_speed, max_speed);
+		let emitter_speed = self
+			.motion
+			.velocity()
+			.dot(direction)
+			.clamp(-max_speed, max_speed);
+		(speed_of_sound - doppler_factor * listener_speed)
+			/ (speed_of_sound - doppler_factor * emitter_speed)
+	}
+
+	/// Makes the emitter recalculate which HRIRs to use.
+	pub fn reset_hrir_weights(&mut self) {
+		self.hrir_direction = None;
+		self.hrir_weights = None;
+	}
+
+	fn update_hrir_weights(&mut self, amount: f64, listener: &Listener, hrir_set: &HrirSet) {
+		let target = listener.local_direction(self.motion.position());
+		let direction = match self.hrir_direction {
+			Some(direction) => {
+				let direction = (direction + (target - direction) * amount).normalized();
+				if direction == Vector3::default() {
+					target
+				} else {
+					direction
+				}
+			}
+			None => target,
+		};
+		self.hrir_direction = Some(direction);
+		let up_to_date = matches!(
+			self.hrir_weights,
+			Some((weights_direction, _)) if weights_direction.dot(direction) >= HRIR_UPDATE_THRESHOLD
+		);
+		if !up_to_date {
+			self.hrir_weights = Some((direction, hrir_set.weights(direction)));
+		}
+	}
+
+	pub fn update(&mut self, dt: f64, listener: &Listener, hrir_set: Option<&HrirSet>) {
+		self.motion.update(dt);
+		let amount = 1.0 - (-dt / SMOOTHING_TIME).exp();
+		match hrir_set {
+			Some(hrir_set) if self.settings.binaural => {
+				self.update_hrir_weights(amount, listener, hrir_set);
+			}
+			_ => self.reset_hrir_weights(),
+		}
+		let hrir_weights = self.hrir_weights.map(|(_, weights)| weights);
+		let target = self.target_output(listener);
+		self.output = Some(match self.output {
+			None => SpatialOutput {
+				hrir_weights,
+				..target
+			},
+			Some(output) => {
+				let approach = |from: f64, to: f64| from + (to - from) * amount;
+				SpatialOutput {
+					volume: approach(output.volume, target.volume),
+					panning: approach(output.panning, target.panning),
+					doppler: approach(output.doppler, target.doppler),
+					cutoff: match (output.cutoff, target.cutoff) {
+						// snap to the target once it's close enough so the
+						// filter can be bypassed at the minimum distance
+						(Some(from), Some(to)) if (to - from).abs() < CUTOFF_SNAP_DISTANCE => {
+							Some(to)
+						}
+						(Some(from), Some(to)) => Some(approach(from, to)),
+						(_, cutoff) => cutoff,
+					},
+					hrir_weights,
+				}
+			}
+		});
+	}
+}
+
+/// A one-pole low-pass filter that makes distant
+/// sounds duller.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct DistanceFilter {
+	state: Frame,
+}
+
+impl DistanceFilter {
+	pub fn new() -> Self {
+		Self {
+			state: Frame::from_mono(0.0),
+		}
+	}
+
+	pub fn process(&mut self, dt: f64, input: Frame, cutoff: f64) -> Frame {
+		// a one-pole filter still noticeably cuts high frequencies
+		// when the cutoff is near the Nyquist frequency, so skip
+		// filtering entirely for emitters that shouldn't be muffled
+		if cutoff >= MAX_DISTANCE_LOW_PASS_CUTOFF.min(0.5 / dt) {
+			self.state = input;
+			return input;
+		}
+		let amount = 1.0 - (-2.0 * std::f64::consts::PI * cutoff * dt).exp();
+		self.state += (input - self.state) * amount as f32;
+		self.state
+	}
+}
@@ -0,0 +1,68 @@
+use super::Vector3;
+
+/// How long (in seconds) it takes a derived velocity to
+/// mostly catch up to a change in speed. This hides jitter
+/// in when position updates reach the audio thread.
+const VELOCITY_SMOOTHING_TIME: f64 = 0.1;
+/// How long (in seconds) an object can go without a position
+/// update before its derived velocity is reset to zero.
+const STATIONARY_TIMEOUT: f64 = 0.5;
+
+/// Tracks the position of the listener or an emitter
+/// and how fast it's moving.
+///
+/// Unless a velocity is set explicitly, the velocity is
+/// derived from how far the position moves between updates.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Motion {
+	position: Vector3,
+	explicit_velocity: Option<Vector3>,
+	derived_velocity: Vector3,
+	time_since_position_change: f64,
+}
+
+impl Motion {
+	pub fn new(position: Vector3) -> Self {
+		Self {
+			position,
+			explicit_velocity: None,
+			derived_velocity: Vector3::default(),
+			time_since_position_change: 0.0,
+		}
+	}
+
+	pub fn position(&self) -> Vector3 {
+		self.position
+	}
+
+	pub fn velocity(&self) -> Vector3 {
+		self.explicit_velocity.unwrap_or(self.derived_velocity)
+	}
+
+	pub fn set_position(&mut self, position: Vector3) {
+		let elapsed = self.time_since_position_change;
+		// several updates can reach the audio thread on the same
+		// frame, in which case only the last one is used
+		if elapsed > 0.0 {
+			if elapsed < STATIONARY_TIMEOUT {
+				let measured_velocity = (position - self.position) * (1.0 / elapsed);
+				let amount = 1.0 - (-elapsed / VELOCITY_SMOOTHING_TIME).exp();
+				self.derived_velocity =
+					self.derived_velocity + (measured_velocity - self.derived_velocity) * amount;
+			}
+			self.time_since_position_change = 0.0;
+		}
+		self.position = position;
+	}
+
+	pub fn set_velocity(&mut self, velocity: Option<Vector3>) {
+		self.explicit_velocity = velocity;
+	}
+
+	pub fn update(&mut self, dt: f64) {
+		self.time_since_position_change += dt;
+		if self.time_since_position_change >= STATIONARY_TIMEOUT {
+			self.derived_velocity = Vector3::default();
+		}
+	}
+}
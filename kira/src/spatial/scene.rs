@@ -0,0 +1,182 @@
+use flume::Sender;
+use indexmap::IndexMap;
+
+use crate::{command::SpatialCommand, resource::Resource};
+
+use super::{Emitter, EmitterId, HrirSet, Listener, SpatialOutput};
+
+/// The listener and all of the currently active emitters.
+pub(crate) struct SpatialScene {
+	listener: Listener,
+	hrir_set: Option<HrirSet>,
+	emitters: IndexMap<EmitterId, Box<Emitter>>,
+}
+
+impl SpatialScene {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			listener: Listener::new(),
+			hrir_set: None,
+			emitters: IndexMap::with_capacity(capacity),
+		}
+	}
+
+	pub fn emitter_output(&self, id: EmitterId) -> Option<SpatialOutput> {
+		self.emitters.get(&id).and_then(|emitter| emitter.output())
+	}
+
+	pub fn hrir_set(&self) -> Option<&HrirSet> {
+		self.hrir_set.as_ref()
+	}
+
+	pub fn run_command(&mut self, command: SpatialCommand, unloader: &mut Sender<Resource>) {
+		match command {
+			SpatialCommand::AddEmitter(id, emitter) => {
+				self.emitters.insert(id, emitter);
+			}
+			SpatialCommand::RemoveEmitter(id) => {
+				if let Some(emitter) = self.emitters.remove(&id) {
+					unloader.try_send(Resource::Emitter(emitter)).ok();
+				}
+			}
+			SpatialCommand::SetEmitterPosition(id, position) => {
+				if let Some(emitter) = self.emitters.get_mut(&id) {
+					emitter.set_position(position);
+				}
+			}
+			SpatialCommand::SetEmitterVelocity(id, velocity) => {
+				if let Some(emitter) = self.emitters.get_mut(&id) {
+					emitter.set_velocity(velocity);
+				}
+			}
+			SpatialCommand::SetListenerPosition(position) => {
+				self.listener.set_position(position);
+			}
+			SpatialCommand::SetListenerVelocity(velocity) => {
+				self.listener.set_velocity(velocity);
+			}
+			SpatialCommand::SetHrirSet(hrir_set) => {
+				if let Some(hrir_set) = std::mem::replace(&mut self.hrir_set, hrir_set) {
+					unloader.try_send(Resource::HrirSet(hrir_set)).ok();
+				}
+				for (_, emitter) in &mut self.emitters {
+					emitter.reset_hrir_weights();
+				}
+			}
+			SpatialCommand::SetListenerOrientation(forward, up) => {
+				self.listener.set_orientation(forward, up);
+			}
+		}
+	}
+
+	pub fn update(&mut self, dt: f64) {
+		self.listener.update(dt);
+		for (_, emitter) in &mut self.emitters {
+			emitter.update(dt, &self.listener, self.hrir_set.as_ref());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use flume::Sender;
+
+	use crate::{
+		command::SpatialCommand,
+		resource::Resource,
+		spatial::{Emitter, EmitterId, EmitterSettings, Vector3},
+	};
+
+	use super::SpatialScene;
+
+	const SPEED_OF_SOUND: f64 = 343.0;
+	const DT: f64 = 1.0 / 1000.0;
+
+	/// Creates a scene with a stationary listener at the origin
+	/// and an emitter far enough in front of it that it doesn't
+	/// reach the listener while moving toward it.
+	fn scene() -> (SpatialScene, EmitterId, Sender<Resource>) {
+		let (mut unloader, _) = flume::unbounded();
+		let mut scene = SpatialScene::new(1);
+		let settings = EmitterSettings::new()
+			.position([0.0, 0.0, -1000.0])
+			.doppler_factor(1.0)
+			.speed_of_sound(SPEED_OF_SOUND);
+		scene.run_command(
+			SpatialCommand::AddEmitter(settings.id, Box::new(Emitter::new(settings))),
+			&mut unloader,
+		);
+		(scene, settings.id, unloader)
+	}
+
+	fn doppler(scene: &SpatialScene, id: EmitterId) -> f64 {
+		scene.emitter_output(id).unwrap().doppler
+	}
+
+	#[test]
+	fn emitter_velocity_raises_pitch() {
+		let (mut scene, id, mut unloader) = scene();
+		scene.run_command(
+			SpatialCommand::SetEmitterVelocity(
+				id,
+				Some(Vector3::new(0.0, 0.0, SPEED_OF_SOUND / 2.0)),
+			),
+			&mut unloader,
+		);
+		scene.update(DT);
+		assert!(
+			(doppler(&scene, id) - 2.0).abs() < 1e-9,
+			"{}",
+			doppler(&scene, id)
+		);
+		// clearing the velocity goes back to the derived velocity,
+		// which is zero because the emitter isn't moving
+		scene.run_command(SpatialCommand::SetEmitterVelocity(id, None), &mut unloader);
+		for _ in 0..1000 {
+			scene.update(DT);
+		}
+		assert!(
+			(doppler(&scene, id) - 1.0).abs() < 1e-6,
+			"{}",
+			doppler(&scene, id)
+		);
+	}
+
+	#[test]
+	fn listener_velocity_raises_pitch() {
+		let (mut scene, id, mut unloader) = scene();
+		scene.run_command(
+			SpatialCommand::SetListenerVelocity(Some(Vector3::new(
+				0.0,
+				0.0,
+				-SPEED_OF_SOUND / 2.0,
+			))),
+			&mut unloader,
+		);
+		scene.update(DT);
+		assert!(
+			(doppler(&scene, id) - 1.5).abs() < 1e-9,
+			"{}",
+			doppler(&scene, id)
+		);
+	}
+
+	#[test]
+	fn velocity_is_derived_from_motion() {
+		let (mut scene, id, mut unloader) = scene();
+		let mut z = -1000.0;
+		for _ in 0..1000 {
+			z += SPEED_OF_SOUND / 2.0 * DT;
+			scene.run_command(
+				SpatialCommand::SetEmitterPosition(id, Vector3::new(0.0, 0.0, z)),
+				&mut unloader,
+			);
+			scene.update(DT);
+		}
+		assert!(
+			(doppler(&scene, id) - 2.0).abs() < 0.01,
+			"{}",
+			doppler(&scene, id)
+		);
+	}
+}
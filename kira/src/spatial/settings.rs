@@ -0,0 +1,198 @@
+use super::{EmitterId, Vector3};
+
+/// How the volume of an emitter decreases as it gets
+/// farther from the listener.
+///
+/// Distances are clamped between the emitter's minimum and
+/// maximum distance, so emitters closer than the minimum distance
+/// play at full volume, and emitters farther than the maximum
+/// distance don't get any quieter.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum AttenuationCurve {
+	/// The volume decreases linearly from full volume at the
+	/// minimum distance to silence at the maximum distance.
+	Linear,
+	/// The volume is inversely proportional to the distance,
+	/// similar to how sound behaves in the real world. Higher
+	/// rolloff factors make the volume decrease faster.
+	Inverse {
+		/// How quickly the volume decreases with distance.
+		rolloff: f64,
+	},
+	/// The volume is proportional to the distance raised to
+	/// the power of `-rolloff`.
+	Exponential {
+		/// How quickly the volume decreases with distance.
+		rolloff: f64,
+	},
+}
+
+impl AttenuationCurve {
+	/// Returns the volume of an emitter at a distance.
+	pub(crate) fn volume(&self, distance: f64, min_distance: f64, max_distance: f64) -> f64 {
+		let min_distance = min_distance.max(f64::EPSILON);
+		let max_distance = max_distance.max(min_distance);
+		let distance = distance.max(min_distance).min(max_distance);
+		match *self {
+			AttenuationCurve::Linear => {
+				if max_distance > min_distance {
+					1.0 - (distance - min_distance) / (max_distance - min_distance)
+				} else {
+					1.0
+				}
+			}
+			AttenuationCurve::Inverse { rolloff } => {
+				min_distance / (min_distance + rolloff * (distance - min_distance))
+			}
+			AttenuationCurve::Exponential { rolloff } => (distance / min_distance).powf(-rolloff),
+		}
+	}
+}
+
+impl Default for AttenuationCurve {
+	fn default() -> Self {
+		Self::Inverse { rolloff: 1.0 }
+	}
+}
+
+/// Settings for an emitter.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(default)
+)]
+pub struct EmitterSettings {
+	/// The unique identifier for the emitter.
+	pub id: EmitterId,
+	/// The starting position of the emitter.
+	pub position: Vector3,
+	/// The distance at which the emitter starts getting quieter.
+	pub min_distance: f64,
+	/// The distance at which the emitter stops getting quieter.
+	pub max_distance: f64,
+	/// How the volume of the emitter decreases with distance.
+	pub attenuation_curve: AttenuationCurve,
+	/// The cutoff frequency (in Hz) of a low-pass filter applied
+	/// when the emitter is at its maximum distance, which makes
+	/// distant sounds duller. If `None`, no filter is applied.
+	pub distance_low_pass: Option<f64>,
+	/// How strongly the pitch of the emitter's instances changes
+	/// when the emitter and listener move toward or away from
+	/// each other. 0 disables the Doppler effect, and 1 is
+	/// physically accurate.
+	pub doppler_factor: f64,
+	/// The speed of sound (in units per second) used to
+	/// calculate the Doppler effect.
+	pub speed_of_sound: f64,
+	/// Whether to render the emitter's instances using
+	/// the listener's HRIR set instead of panning them.
+	///
+	/// Only the number of instances set by
+	/// [`AudioManagerSettings::num_binaural_instances`](crate::manager::AudioManagerSettings::num_binaural_instances)
+	/// can be rendered binaurally at a time.
+	pub binaural: bool,
+}
+
+impl EmitterSettings {
+	/// Creates a new `EmitterSettings` with the default settings.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the unique identifier for the emitter.
+	pub fn id(self, id: impl Into<EmitterId>) -> Self {
+		Self {
+			id: id.into(),
+			..self
+		}
+	}
+
+	/// Sets the starting position of the emitter.
+	pub fn position(self, position: impl Into<Vector3>) -> Self {
+		Self {
+			position: position.into(),
+			..self
+		}
+	}
+
+	/// Sets the distance at which the emitter starts getting quieter.
+	pub fn min_distance(self, min_distance: f64) -> Self {
+		Self {
+			min_distance,
+			..self
+		}
+	}
+
+	/// Sets the distance at which the emitter stops getting quieter.
+	pub fn max_distance(self, max_distance: f64) -> Self {
+		Self {
+			max_distance,
+			..self
+		}
+	}
+
+	/// Sets how the volume of the emitter decreases with distance.
+	pub fn attenuation_curve(self, attenuation_curve: AttenuationCurve) -> Self {
+		Self {
+			attenuation_curve,
+			..self
+		}
+	}
+
+	/// Sets how strongly the pitch of the emitter's instances
+	/// changes due to the Doppler effect.
+	pub fn doppler_factor(self, doppler_factor: f64) -> Self {
+		Self {
+			doppler_factor,
+			..self
+		}
+	}
+
+	/// Sets the speed of sound (in units per second) used to
+	/// calculate the Doppler effect.
+	pub fn speed_of_sound(self, speed_of_sound: f64) -> Self {
+		Self {
+			speed_of_sound,
+			..self
+		}
+	}
+
+	/// Renders the emitter's instances using the listener's
+	/// HRIR set instead of panning them.
+	pub fn binaural(self) -> Self {
+		Self {
+			binaural: true,
+			..self
+		}
+	}
+
+	/// Sets the cutoff frequency (in Hz) of the low-pass filter
+	/// applied when the emitter is at its maximum distance.
+	pub fn distance_low_pass(self, cutoff: f64) -> Self {
+		Self {
+			distance_low_pass: Some(cutoff),
+			..self
+		}
+	}
+}
+
+impl Default for EmitterSettings {
+	fn default() -> Self {
+		Self {
+			id: EmitterId::new(),
+			position: Vector3::default(),
+			min_distance: 1.0,
+			max_distance: 100.0,
+			attenuation_curve: AttenuationCurve::default(),
+			distance_low_pass: None,
+			doppler_factor: 0.0,
+			speed_of_sound: 343.0,
+			binaural: false,
+		}
+	}
+}
@@ -0,0 +1,98 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// A position or direction in 3D space.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(
+	feature = "serde_support",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct Vector3 {
+	/// The x component of the vector.
+	pub x: f64,
+	/// The y component of the vector.
+	pub y: f64,
+	/// The z component of the vector.
+	pub z: f64,
+}
+
+impl Vector3 {
+	/// Creates a new vector.
+	pub fn new(x: f64, y: f64, z: f64) -> Self {
+		Self { x, y, z }
+	}
+
+	/// Returns the dot product of this vector and another vector.
+	pub fn dot(self, other: Self) -> f64 {
+		self.x * other.x + self.y * other.y + self.z * other.z
+	}
+
+	/// Returns the cross product of this vector and another vector.
+	pub fn cross(self, other: Self) -> Self {
+		Self {
+			x: self.y * other.z - self.z * other.y,
+			y: self.z * other.x - self.x * other.z,
+			z: self.x * other.y - self.y * other.x,
+		}
+	}
+
+	/// Returns the length of the vector.
+	pub fn length(self) -> f64 {
+		self.dot(self).sqrt()
+	}
+
+	/// Returns a vector with the same direction as this one
+	/// and a length of 1, or a zero vector if this vector
+	/// has no length.
+	pub fn normalized(self) -> Self {
+		let length = self.length();
+		if length > 0.0 {
+			self * (1.0 / length)
+		} else {
+			Self::default()
+		}
+	}
+}
+
+impl From<[f64; 3]> for Vector3 {
+	fn from(components: [f64; 3]) -> Self {
+		Self::new(components[0], components[1], components[2])
+	}
+}
+
+impl From<(f64, f64, f64)> for Vector3 {
+	fn from((x, y, z): (f64, f64, f64)) -> Self {
+		Self::new(x, y, z)
+	}
+}
+
+impl Add for Vector3 {
+	type Output = Self;
+
+	fn add(self, rhs: Self) -> Self::Output {
+		Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+	}
+}
+
+impl Sub for Vector3 {
+	type Output = Self;
+
+	fn sub(self, rhs: Self) -> Self::Output {
+		Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+	}
+}
+
+impl Mul<f64> for Vector3 {
+	type Output = Self;
+
+	fn mul(self, rhs: f64) -> Self::Output {
+		Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+	}
+}
+
+impl Neg for Vector3 {
+	type Output = Self;
+
+	fn neg(self) -> Self::Output {
+		Self::new(-self.x, -self.y, -self.z)
+	}
+}
@@ -12,6 +12,12 @@ impl Tempo {
 	pub fn beats_to_seconds(&self, beats: f64) -> f64 {
 		(60.0 / self.0) * beats
 	}
+
+	/// Converts a length of time in seconds to a number
+	/// of beats at this tempo.
+	pub fn seconds_to_beats(&self, seconds: f64) -> f64 {
+		seconds * (self.0 / 60.0)
+	}
 }
 
 impl From<f64> for Tempo {